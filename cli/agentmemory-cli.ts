@@ -100,7 +100,8 @@ cli
   .argument("<agent_id>", "Agent identifier")
   .argument("<input_data>", "Input context (max 256 chars)")
   .argument("<logic_data>", "Decision logic (max 256 chars)")
-  .action(async (agentId, inputData, logicData) => {
+  .option("--domain <domain>", "Reputation domain (e.g. trading)", "")
+  .action(async (agentId, inputData, logicData, options) => {
     try {
       const opts = cli.opts();
       const client = createClient(opts.rpc, opts.keypair, opts.programId);
@@ -109,7 +110,12 @@ cli
       console.log(`  Input: ${inputData}`);
       console.log(`  Logic: ${logicData}`);
 
-      const result = await client.logDecision(agentId, inputData, logicData);
+      const result = await client.logDecision(
+        agentId,
+        inputData,
+        logicData,
+        options.domain
+      );
 
       console.log(`Done. Tx: ${result.tx}`);
       console.log(`Memory log: ${result.memoryLogPubkey.toBase58()}`);
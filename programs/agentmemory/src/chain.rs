@@ -0,0 +1,67 @@
+use anchor_lang::solana_program::keccak;
+
+/// Per-agent hash chain over logged decisions.
+///
+/// Every `MemoryLog` and `DecisionBatch` records the agent's chain head at the
+/// time it was written (`prev_root`) and the address of the previous entry
+/// (`prev_log`). Its own link commits to that head, its Merkle root and the
+/// sequence range it covers, and becomes the agent's new `chain_head`. Leaving
+/// a decision out, or reordering entries, changes every later link.
+pub fn link(
+    prev_root: &[u8; 32],
+    merkle_root: &[u8; 32],
+    first_sequence: u64,
+    last_sequence: u64,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        prev_root,
+        merkle_root,
+        &first_sequence.to_le_bytes(),
+        &last_sequence.to_le_bytes(),
+    ])
+    .to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_hashes_head_root_and_range_in_order() {
+        let prev_root = [1; 32];
+        let merkle_root = [2; 32];
+        let mut preimage = Vec::new();
+        preimage.extend_from_slice(&prev_root);
+        preimage.extend_from_slice(&merkle_root);
+        preimage.extend_from_slice(&5u64.to_le_bytes());
+        preimage.extend_from_slice(&7u64.to_le_bytes());
+        assert_eq!(
+            link(&prev_root, &merkle_root, 5, 7),
+            keccak::hash(&preimage).to_bytes()
+        );
+    }
+
+    #[test]
+    fn link_commits_to_every_input() {
+        let base = link(&[1; 32], &[2; 32], 5, 7);
+        assert_ne!(base, link(&[0; 32], &[2; 32], 5, 7));
+        assert_ne!(base, link(&[1; 32], &[3; 32], 5, 7));
+        assert_ne!(base, link(&[1; 32], &[2; 32], 6, 7));
+        assert_ne!(base, link(&[1; 32], &[2; 32], 5, 8));
+        // Swapping the head and root is a different link.
+        assert_ne!(base, link(&[2; 32], &[1; 32], 5, 7));
+    }
+
+    #[test]
+    fn dropping_an_entry_changes_the_head() {
+        let roots = [[1; 32], [2; 32], [3; 32]];
+        let full =
+            roots.iter().enumerate().fold([0; 32], |head, (seq, root)| {
+                link(&head, root, seq as u64, seq as u64)
+            });
+        let skipped = [(0, roots[0]), (2, roots[2])]
+            .iter()
+            .fold([0; 32], |head, (seq, root)| link(&head, root, *seq, *seq));
+        assert_ne!(full, skipped);
+    }
+}
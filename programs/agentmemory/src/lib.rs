@@ -1,7 +1,16 @@
+// Anchor 0.30 macros emit cfgs (custom-heap, target_os = "solana", ...) that
+// newer toolchains flag as unexpected.
+#![allow(unexpected_cfgs)]
+
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::keccak;
-use anchor_lang::solana_program::program::invoke;
+use anchor_lang::solana_program::program::{invoke, set_return_data};
 use anchor_lang::solana_program::system_instruction;
+use anchor_lang::Discriminator;
+
+pub mod chain;
+pub mod merkle;
+pub mod reputation;
 
 declare_id!("EivtLAsC6pB2DJHd1MdSC9nYByVzcowJoUvqh9GmAjHc");
 
@@ -13,57 +22,558 @@ pub mod agentmemory {
         ctx: Context<InitializeAgent>,
         agent_id: String,
     ) -> Result<()> {
-        require!(agent_id.len() <= 64, ErrorCode::AgentIdTooLong);
+        validate_agent_id(&agent_id)?;
+        check_name_available(
+            &agent_id,
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.platform_config,
+            &ctx.accounts.name_reservation,
+        )?;
 
         let agent = &mut ctx.accounts.agent;
         agent.agent_id = agent_id;
         agent.authority = ctx.accounts.authority.key();
+        agent.created_at = Clock::get()?.unix_timestamp;
         agent.reputation = 0;
+        agent.total_positive = 0;
+        agent.total_negative = 0;
+        agent.success_count = 0;
+        agent.failure_count = 0;
+        agent.streak = 0;
         agent.total_logs = 0;
         agent.total_attestations = 0;
+        agent.chain_head = [0; 32];
+        agent.last_log = Pubkey::default();
+        agent.last_reputation_update = Clock::get()?.unix_timestamp;
+        agent.has_reputation_history = false;
+        agent.endorsement_score = 0;
+        agent.endorsement_count = 0;
+        agent.pending_authority = None;
+        agent.recovery_authority = None;
+        agent.profile = AgentProfile::default();
+        agent.is_retired = false;
+        agent.retired_at = 0;
+        agent.organization = None;
+        agent.org_reported_reputation = 0;
+        agent.org_reported_logs = 0;
+        agent.is_frozen = false;
+        agent.moderation_reason = 0;
+        agent.moderation_evidence_hash = [0; 32];
+        agent.moderated_at = 0;
+        agent.appeal_hash = None;
+        agent.appealed_at = 0;
+        agent.active_version = 0;
+        agent.authority_epoch = 0;
+        agent.legacy_logs = 0;
+        agent.migrated_logs = 0;
+        agent.last_migrated_timestamp = 0;
+        agent.org_spent = 0;
         agent.bump = ctx.bumps.agent;
 
+        if let Some(organization) = ctx.accounts.organization.as_mut() {
+            require!(
+                organization.admins.contains(&agent.authority),
+                ErrorCode::NotOrganizationAdmin
+            );
+            agent.organization = Some(organization.key());
+            organization.agent_count += 1;
+        }
+
         msg!("Agent initialized: {}", agent.agent_id);
         Ok(())
     }
 
+    /// Log a decision. `domain` tags it with a reputation category such as
+    /// "trading" (empty for none); see `DomainReputation`.
     pub fn log_decision(
         ctx: Context<LogDecision>,
         input_data: String,
         logic_data: String,
+        domain: String,
     ) -> Result<()> {
         require!(input_data.len() <= 256, ErrorCode::InputTooLong);
         require!(logic_data.len() <= 256, ErrorCode::LogicTooLong);
 
-        let agent = &mut ctx.accounts.agent;
+        let input_hash = keccak::hash(input_data.as_bytes()).to_bytes();
+        let logic_hash = keccak::hash(logic_data.as_bytes()).to_bytes();
+        let bump = ctx.bumps.memory_log;
+        record_decision(
+            ctx.accounts,
+            bump,
+            input_hash,
+            logic_hash,
+            domain,
+            false,
+        )
+    }
+
+    /// Log a decision from caller-computed commitments instead of plaintext,
+    /// so confidential inputs never appear in transaction data. Each
+    /// commitment is `keccak(salt || data)` (see `decision_commitment`) and
+    /// is opened later with `reveal_decision`.
+    ///
+    /// `reveal_after` and `reveal_deadline` bound when the reveal may happen
+    /// (0 = no bound), e.g. to seal a prediction until an event has passed.
+    pub fn log_decision_commitment(
+        ctx: Context<LogDecision>,
+        input_commitment: [u8; 32],
+        logic_commitment: [u8; 32],
+        domain: String,
+        reveal_after: i64,
+        reveal_deadline: i64,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(reveal_after >= 0, ErrorCode::InvalidRevealWindow);
+        require!(
+            reveal_deadline == 0
+                || (reveal_deadline > reveal_after && reveal_deadline > now),
+            ErrorCode::InvalidRevealWindow
+        );
+
+        let bump = ctx.bumps.memory_log;
+        record_decision(
+            ctx.accounts,
+            bump,
+            input_commitment,
+            logic_commitment,
+            domain,
+            true,
+        )?;
+
+        let memory_log = &mut ctx.accounts.memory_log;
+        memory_log.reveal_after = reveal_after;
+        memory_log.reveal_deadline = reveal_deadline;
+        Ok(())
+    }
+
+    /// Open a committed decision by supplying the plaintext and salts that
+    /// produce its stored `input_hash` and `logic_hash`.
+    pub fn reveal_decision(
+        ctx: Context<RevealDecision>,
+        input_data: String,
+        logic_data: String,
+        input_salt: [u8; 32],
+        logic_salt: [u8; 32],
+    ) -> Result<()> {
+        require!(input_data.len() <= 256, ErrorCode::InputTooLong);
+        require!(logic_data.len() <= 256, ErrorCode::LogicTooLong);
+
+        let memory_log = &mut ctx.accounts.memory_log;
+        let now = Clock::get()?.unix_timestamp;
+        require!(memory_log.is_commitment, ErrorCode::NotACommitment);
+        require!(!memory_log.is_revealed, ErrorCode::AlreadyRevealed);
+        require!(now >= memory_log.reveal_after, ErrorCode::RevealTooEarly);
+        require!(
+            memory_log.reveal_deadline == 0
+                || now <= memory_log.reveal_deadline,
+            ErrorCode::RevealWindowClosed
+        );
+        require!(
+            decision_commitment(&input_salt, input_data.as_bytes())
+                == memory_log.input_hash
+                && decision_commitment(&logic_salt, logic_data.as_bytes())
+                    == memory_log.logic_hash,
+            ErrorCode::RevealMismatch
+        );
+
+        memory_log.is_revealed = true;
+
+        emit!(DecisionRevealed {
+            agent: memory_log.agent,
+            memory_log: memory_log.key(),
+            sequence: memory_log.sequence,
+            input_data,
+            logic_data,
+            timestamp: now,
+        });
+
+        msg!("Decision revealed: seq={}", memory_log.sequence);
+        Ok(())
+    }
+
+    /// Permissionless crank that flags a committed decision whose reveal
+    /// deadline passed without a reveal. `attest_outcome` penalises flagged
+    /// logs (and flags them itself if nobody ran this first).
+    pub fn flag_missed_reveal(ctx: Context<FlagMissedReveal>) -> Result<()> {
         let memory_log = &mut ctx.accounts.memory_log;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            !memory_log.reveal_missed && memory_log.is_reveal_overdue(now),
+            ErrorCode::RevealNotMissed
+        );
+
+        memory_log.reveal_missed = true;
+
+        emit!(RevealMissed {
+            agent: memory_log.agent,
+            memory_log: memory_log.key(),
+            sequence: memory_log.sequence,
+            reveal_deadline: memory_log.reveal_deadline,
+        });
+
+        msg!("Reveal missed: seq={}", memory_log.sequence);
+        Ok(())
+    }
+
+    /// Log a batch of decisions under a single Merkle root. Each leaf is the
+    /// pair of input/logic hashes the caller would otherwise pass to
+    /// `log_decision`; the batch consumes one sequence number per leaf.
+    pub fn log_decision_batch(
+        ctx: Context<LogDecisionBatch>,
+        leaves: Vec<DecisionLeaf>,
+    ) -> Result<()> {
+        require!(!leaves.is_empty(), ErrorCode::EmptyBatch);
+        require!(leaves.len() <= MAX_BATCH_LEAVES, ErrorCode::BatchTooLarge);
+
+        let agent = &mut ctx.accounts.agent;
+        let batch = &mut ctx.accounts.batch;
         let clock = Clock::get()?;
 
-        let input_hash = keccak::hash(input_data.as_bytes());
-        let logic_hash = keccak::hash(logic_data.as_bytes());
+        let leaf_hashes: Vec<[u8; 32]> = leaves
+            .iter()
+            .map(|leaf| merkle::hash_leaf(&leaf.input_hash, &leaf.logic_hash))
+            .collect();
+        let merkle_root =
+            merkle::compute_root(&leaf_hashes).ok_or(ErrorCode::EmptyBatch)?;
 
-        let mut combined = Vec::new();
-        combined.extend_from_slice(input_hash.as_ref());
-        combined.extend_from_slice(logic_hash.as_ref());
-        let merkle_root = keccak::hash(&combined);
+        let leaf_count = leaves.len() as u32;
+        let first_sequence = agent.total_logs;
+        let last_sequence = first_sequence + leaf_count as u64 - 1;
 
-        memory_log.agent = agent.key();
-        memory_log.input_hash = input_hash.to_bytes();
-        memory_log.logic_hash = logic_hash.to_bytes();
-        memory_log.merkle_root = merkle_root.to_bytes();
-        memory_log.timestamp = clock.unix_timestamp;
-        memory_log.is_attested = false;
-        memory_log.bump = ctx.bumps.memory_log;
+        batch.agent = agent.key();
+        batch.merkle_root = merkle_root;
+        batch.leaf_count = leaf_count;
+        batch.first_sequence = first_sequence;
+        batch.last_sequence = last_sequence;
+        batch.timestamp = clock.unix_timestamp;
+        batch.prev_log = agent.last_log;
+        batch.prev_root = agent.chain_head;
+        batch.version = agent.active_version;
+        batch.bump = ctx.bumps.batch;
 
-        agent.total_logs += 1;
+        agent.chain_head = chain::link(
+            &batch.prev_root,
+            &merkle_root,
+            first_sequence,
+            last_sequence,
+        );
+        agent.last_log = batch.key();
+        agent.total_logs = last_sequence + 1;
 
-        emit!(DecisionLogged {
+        emit!(DecisionBatchLogged {
             agent: agent.key(),
-            merkle_root: merkle_root.to_bytes(),
+            batch: batch.key(),
+            merkle_root,
+            leaf_count,
+            first_sequence,
+            last_sequence,
+            chain_head: agent.chain_head,
             timestamp: clock.unix_timestamp,
         });
 
-        msg!("Decision logged for agent: {}", agent.agent_id);
+        msg!(
+            "Decision batch logged for agent: {} (seq {}..={})",
+            agent.agent_id,
+            first_sequence,
+            last_sequence
+        );
+        Ok(())
+    }
+
+    /// Prove that a decision belongs to a logged root. Pass exactly one of
+    /// `memory_log` or `batch`; `leaf_index` is the position within the batch
+    /// (always 0 for a single log). Fails unless the proof checks out, so
+    /// other programs can CPI into this before trusting an agent's reasoning.
+    pub fn verify_decision_inclusion(
+        ctx: Context<VerifyDecisionInclusion>,
+        input_hash: [u8; 32],
+        logic_hash: [u8; 32],
+        leaf_index: u32,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let (agent, root_account, merkle_root, leaf_count, first_sequence) =
+            match (&ctx.accounts.memory_log, &ctx.accounts.batch) {
+                (Some(log), None) => {
+                    (log.agent, log.key(), log.merkle_root, 1, log.sequence)
+                }
+                (None, Some(batch)) => (
+                    batch.agent,
+                    batch.key(),
+                    batch.merkle_root,
+                    batch.leaf_count,
+                    batch.first_sequence,
+                ),
+                _ => return err!(ErrorCode::InvalidRootAccount),
+            };
+
+        let leaf = merkle::hash_leaf(&input_hash, &logic_hash);
+        require!(
+            merkle::verify_proof(
+                &leaf,
+                leaf_index,
+                leaf_count,
+                &proof,
+                &merkle_root
+            ),
+            ErrorCode::InvalidInclusionProof
+        );
+
+        emit!(DecisionVerified {
+            agent,
+            root_account,
+            merkle_root,
+            leaf,
+            sequence: first_sequence + leaf_index as u64,
+        });
+        Ok(())
+    }
+
+    /// Check that the logs and batches passed as remaining accounts, oldest
+    /// first, form an unbroken stretch of the agent's decision chain: each
+    /// entry points at the previous one, commits to its link, and continues
+    /// its sequence range without gaps.
+    pub fn verify_log_chain<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VerifyLogChain<'info>>,
+    ) -> Result<()> {
+        let agent = &ctx.accounts.agent;
+        require!(
+            !ctx.remaining_accounts.is_empty(),
+            ErrorCode::BrokenLogChain
+        );
+
+        let mut previous: Option<ChainEntry> = None;
+        for info in ctx.remaining_accounts.iter() {
+            let entry = ChainEntry::load(info)?;
+            require_keys_eq!(
+                entry.agent,
+                agent.key(),
+                ErrorCode::BrokenLogChain
+            );
+
+            if let Some(prev) = &previous {
+                require_keys_eq!(
+                    entry.prev_log,
+                    prev.key,
+                    ErrorCode::BrokenLogChain
+                );
+                require!(
+                    entry.prev_root == prev.link()
+                        && entry.first_sequence == prev.last_sequence + 1,
+                    ErrorCode::BrokenLogChain
+                );
+            }
+            previous = Some(entry);
+        }
+
+        let first = ChainEntry::load(&ctx.remaining_accounts[0])?;
+        let last = previous.ok_or(ErrorCode::BrokenLogChain)?;
+        let reaches_head =
+            last.key == agent.last_log && last.link() == agent.chain_head;
+
+        emit!(LogChainVerified {
+            agent: agent.key(),
+            first_log: first.key,
+            last_log: last.key,
+            first_sequence: first.first_sequence,
+            last_sequence: last.last_sequence,
+            reaches_head,
+        });
+
+        msg!(
+            "Log chain verified for agent: {} (seq {}..={})",
+            agent.agent_id,
+            first.first_sequence,
+            last.last_sequence
+        );
+        Ok(())
+    }
+
+    /// Upgrade an `AgentAccount` created before decision chaining to the
+    /// current layout. Counters and reputation are kept; the decision chain
+    /// starts fresh from the first log written after the upgrade. The legacy
+    /// score only ever held net positive history, so it seeds both the signed
+    /// reputation and `total_positive`; outcome counts start at zero. The
+    /// creation time was never recorded, so account age counts from the
+    /// migration.
+    pub fn migrate_agent(
+        ctx: Context<MigrateAgent>,
+        _agent_id: String,
+    ) -> Result<()> {
+        let agent_info = ctx.accounts.agent.to_account_info();
+
+        let legacy = {
+            let data = agent_info.try_borrow_data()?;
+            require!(
+                data.len() == LegacyAgentAccount::LEN
+                    && data[..8] == AgentAccount::DISCRIMINATOR,
+                ErrorCode::NotLegacyAgent
+            );
+            LegacyAgentAccount::deserialize(&mut &data[8..])?
+        };
+        require_keys_eq!(
+            legacy.authority,
+            ctx.accounts.authority.key(),
+            ErrorCode::NotLegacyAgent
+        );
+
+        resize_account(
+            &agent_info,
+            AgentAccount::LEN,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let migrated = AgentAccount {
+            agent_id: legacy.agent_id,
+            authority: legacy.authority,
+            created_at: Clock::get()?.unix_timestamp,
+            reputation: i64::try_from(legacy.reputation).unwrap_or(i64::MAX),
+            total_positive: legacy.reputation,
+            total_negative: 0,
+            success_count: 0,
+            failure_count: 0,
+            streak: 0,
+            total_logs: legacy.total_logs,
+            total_attestations: legacy.total_attestations,
+            chain_head: [0; 32],
+            last_log: Pubkey::default(),
+            last_reputation_update: Clock::get()?.unix_timestamp,
+            has_reputation_history: false,
+            endorsement_score: 0,
+            endorsement_count: 0,
+            pending_authority: None,
+            recovery_authority: None,
+            profile: AgentProfile::default(),
+            is_retired: false,
+            retired_at: 0,
+            organization: None,
+            org_reported_reputation: 0,
+            org_reported_logs: 0,
+            is_frozen: false,
+            moderation_reason: 0,
+            moderation_evidence_hash: [0; 32],
+            moderated_at: 0,
+            appeal_hash: None,
+            appealed_at: 0,
+            active_version: 0,
+            authority_epoch: 0,
+            legacy_logs: legacy.total_logs,
+            migrated_logs: 0,
+            last_migrated_timestamp: 0,
+            org_spent: 0,
+            bump: legacy.bump,
+        };
+        let mut data = agent_info.try_borrow_mut_data()?;
+        migrated.try_serialize(&mut &mut data[..])?;
+
+        msg!("Legacy agent migrated: {}", migrated.agent_id);
+        Ok(())
+    }
+
+    /// Upgrade a `MemoryLog` created under the legacy `[b"memory", agent, timestamp]`
+    /// seeds to the current layout. The account keeps its address (and any
+    /// attestation pointing at it). `sequence` is the log's rank by timestamp
+    /// among the agent's legacy logs, i.e. the sequence it would have had
+    /// under counter-based addressing. It must equal the agent's migration
+    /// cursor, so logs are migrated oldest first and a skipped log is
+    /// rejected instead of shifting every later sequence. A migrated log is
+    /// not at its sequence-seeded address; the SDK's `findMemoryLog` finds it
+    /// with a `getProgramAccounts` filter on the `sequence` field.
+    pub fn migrate_memory_log(
+        ctx: Context<MigrateMemoryLog>,
+        sequence: u64,
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        let legacy_info = ctx.accounts.legacy_memory_log.to_account_info();
+
+        let legacy = {
+            let data = legacy_info.try_borrow_data()?;
+            require!(
+                data.len() == LegacyMemoryLog::LEN
+                    && data[..8] == MemoryLog::DISCRIMINATOR,
+                ErrorCode::NotLegacyMemoryLog
+            );
+            LegacyMemoryLog::deserialize(&mut &data[8..])?
+        };
+
+        require_keys_eq!(
+            legacy.agent,
+            agent.key(),
+            ErrorCode::NotLegacyMemoryLog
+        );
+        let expected = Pubkey::create_program_address(
+            &[
+                b"memory",
+                agent.key().as_ref(),
+                &legacy.timestamp.to_le_bytes(),
+                &[legacy.bump],
+            ],
+            ctx.program_id,
+        )
+        .map_err(|_| error!(ErrorCode::NotLegacyMemoryLog))?;
+        require_keys_eq!(
+            expected,
+            legacy_info.key(),
+            ErrorCode::NotLegacyMemoryLog
+        );
+        require!(
+            agent.migrated_logs < agent.legacy_logs,
+            ErrorCode::LegacyLogsMigrated
+        );
+        require!(
+            sequence == agent.migrated_logs,
+            ErrorCode::LegacyLogSequenceMismatch
+        );
+        require!(
+            agent.migrated_logs == 0
+                || legacy.timestamp > agent.last_migrated_timestamp,
+            ErrorCode::LegacyLogOutOfOrder
+        );
+        agent.migrated_logs += 1;
+        agent.last_migrated_timestamp = legacy.timestamp;
+
+        resize_account(
+            &legacy_info,
+            MemoryLog::LEN,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let migrated = MemoryLog {
+            agent: legacy.agent,
+            sequence,
+            input_hash: legacy.input_hash,
+            logic_hash: legacy.logic_hash,
+            merkle_root: legacy.merkle_root,
+            timestamp: legacy.timestamp,
+            is_attested: legacy.is_attested,
+            is_commitment: false,
+            is_revealed: true,
+            reveal_after: 0,
+            reveal_deadline: 0,
+            reveal_missed: false,
+            domain: String::new(),
+            prev_log: Pubkey::default(),
+            prev_root: [0; 32],
+            version: 0,
+            version_tallied: false,
+            bump: legacy.bump,
+        };
+        let mut data = legacy_info.try_borrow_mut_data()?;
+        migrated.try_serialize(&mut &mut data[..])?;
+
+        emit!(MemoryLogMigrated {
+            agent: agent.key(),
+            memory_log: legacy_info.key(),
+            legacy_timestamp: legacy.timestamp,
+            sequence,
+        });
+
+        msg!(
+            "Legacy memory log migrated: {} (seq={})",
+            legacy_info.key(),
+            sequence
+        );
         Ok(())
     }
 
@@ -78,530 +588,6254 @@ pub mod agentmemory {
             !ctx.accounts.memory_log.is_attested,
             ErrorCode::AlreadyAttested
         );
+        check_attester_allowlist(
+            &ctx.accounts.attester_allowlist,
+            &ctx.accounts.attester.key(),
+        )?;
 
         let agent = &mut ctx.accounts.agent;
         let memory_log = &mut ctx.accounts.memory_log;
         let attestation = &mut ctx.accounts.attestation;
         let clock = Clock::get()?;
 
+        // Elo needs an opponent; without the attester's linked agent it
+        // would always score an even match.
+        if let ScoringModel::Elo { .. } =
+            ctx.accounts.platform_config.scoring_model
+        {
+            require!(
+                ctx.accounts.attester_agent.is_some(),
+                ErrorCode::MissingAttesterAgent
+            );
+        }
+
         let outcome_hash = keccak::hash(outcome_data.as_bytes());
 
+        let (success, score_delta) = memory_log.penalise_missed_reveal(
+            clock.unix_timestamp,
+            success,
+            score_delta,
+        );
+
         attestation.memory_log = memory_log.key();
+        attestation.attester = ctx.accounts.attester.key();
         attestation.outcome_hash = outcome_hash.to_bytes();
         attestation.success = success;
         attestation.score_delta = score_delta;
+        attestation.attester_reputation =
+            ctx.accounts.attester_agent.as_ref().map(|attester_agent| {
+                attester_agent.current_reputation(
+                    ctx.accounts.platform_config.decay_half_life(),
+                    clock.unix_timestamp,
+                )
+            });
+        attestation.applied_delta = 0;
         attestation.timestamp = clock.unix_timestamp;
+        attestation.status = AttestationStatus::Pending;
+        attestation.dispute_deadline = clock.unix_timestamp
+            + ctx.accounts.platform_config.dispute_window_secs;
         attestation.bump = ctx.bumps.attestation;
 
         memory_log.is_attested = true;
 
-        let new_reputation = (agent.reputation as i64)
-            .checked_add(score_delta)
-            .ok_or(ErrorCode::ReputationOverflow)?;
-        agent.reputation = new_reputation.max(0) as u64;
-        agent.total_attestations += 1;
+        let attester_account = &mut ctx.accounts.attester_account;
+        attester_account.total_attestations += 1;
+        attester_account.last_attested_at = clock.unix_timestamp;
 
         emit!(OutcomeAttested {
             agent: agent.key(),
             memory_log: memory_log.key(),
+            attestation: attestation.key(),
             success,
-            new_reputation: agent.reputation,
+            score_delta,
+            dispute_deadline: attestation.dispute_deadline,
         });
 
+        let config = &ctx.accounts.platform_config;
+        if agent.refresh_reputation(config, clock.unix_timestamp) {
+            record_checkpoint(
+                agent,
+                config,
+                ctx.accounts.reputation_history.as_mut(),
+            )?;
+        }
+
+        // With no dispute window the outcome applies immediately.
+        if config.dispute_window_secs == 0 {
+            finalize_attestation_outcome(
+                agent,
+                ctx.accounts.domain_reputation.as_mut(),
+                ctx.accounts.reputation_history.as_mut(),
+                memory_log,
+                attestation,
+                config,
+                clock.unix_timestamp,
+            )?;
+        }
+
         msg!(
-            "Outcome attested for agent: {}, new reputation: {}",
+            "Outcome attested for agent: {} (disputable until {})",
             agent.agent_id,
-            agent.reputation
+            attestation.dispute_deadline
         );
         Ok(())
     }
 
-    pub fn initialize_platform(
-        ctx: Context<InitializePlatform>,
-        treasury: Pubkey,
-        platform_fee_bps: u16,
-        referral_fee_bps: u16,
+    /// Open a quorum attestation round on a memory log: instead of a single
+    /// attester, `quorum` registered attesters vote and the outcome is
+    /// settled only once all votes are in. Only the platform authority or a
+    /// registered attester other than the agent's authority may open one,
+    /// and a round that misses quorum within `ROUND_EXPIRY_SECS` can be
+    /// cancelled.
+    pub fn open_attestation_round(
+        ctx: Context<OpenAttestationRound>,
+        quorum: u8,
     ) -> Result<()> {
-        require!(platform_fee_bps <= 1000, ErrorCode::FeeTooHigh);
-        require!(referral_fee_bps <= 1000, ErrorCode::FeeTooHigh);
+        require!(
+            (2..=MAX_ROUND_VOTERS as u8).contains(&quorum),
+            ErrorCode::InvalidQuorum
+        );
+        require!(
+            !ctx.accounts.memory_log.is_attested,
+            ErrorCode::AlreadyAttested
+        );
+        let opener = ctx.accounts.opener.key();
+        let is_attester =
+            ctx.accounts
+                .opener_attester
+                .as_ref()
+                .is_some_and(|account| {
+                    account.is_active
+                        && account.attester != ctx.accounts.agent.authority
+                });
+        require!(
+            opener == ctx.accounts.platform_config.authority || is_attester,
+            ErrorCode::UnauthorizedRoundOpener
+        );
 
-        let config = &mut ctx.accounts.platform_config;
-        config.authority = ctx.accounts.authority.key();
-        config.treasury = treasury;
-        config.platform_fee_bps = platform_fee_bps;
-        config.referral_fee_bps = referral_fee_bps;
-        config.bump = ctx.bumps.platform_config;
+        let memory_log = &mut ctx.accounts.memory_log;
+        let round = &mut ctx.accounts.round;
+        round.memory_log = memory_log.key();
+        round.agent = ctx.accounts.agent.key();
+        round.opener = opener;
+        round.quorum = quorum;
+        round.votes = Vec::new();
+        round.is_finalized = false;
+        round.success = false;
+        round.score_delta = 0;
+        round.opened_at = Clock::get()?.unix_timestamp;
+        round.finalized_at = 0;
+        round.bump = ctx.bumps.round;
+
+        // Blocks single-attester `attest_outcome` on the same log.
+        memory_log.is_attested = true;
+
+        emit!(AttestationRoundOpened {
+            agent: round.agent,
+            memory_log: round.memory_log,
+            round: round.key(),
+            quorum,
+        });
 
         msg!(
-            "Platform initialized: treasury={}, fee={}bps",
-            treasury,
-            platform_fee_bps
+            "Attestation round opened: {} (quorum={})",
+            round.key(),
+            quorum
         );
         Ok(())
     }
 
-    pub fn register_module(
-        ctx: Context<RegisterModule>,
-        module_id: String,
-        price_lamports: u64,
-        royalty_bps: u16,
-        ipfs_hash: String,
+    /// Cast one attester's vote in a round. Once `quorum` votes are in, any
+    /// voter from the majority can `settle_attestation_round`.
+    pub fn submit_round_vote(
+        ctx: Context<SubmitRoundVote>,
+        success: bool,
+        score_delta: i64,
     ) -> Result<()> {
-        require!(module_id.len() <= 64, ErrorCode::ModuleIdTooLong);
-        require!(ipfs_hash.len() <= 128, ErrorCode::IpfsHashTooLong);
-        require!(royalty_bps <= 10000, ErrorCode::InvalidRoyalty);
-        require!(price_lamports >= 1_000_000, ErrorCode::PriceTooLow);
+        check_attester_allowlist(
+            &ctx.accounts.attester_allowlist,
+            &ctx.accounts.attester.key(),
+        )?;
 
-        let module = &mut ctx.accounts.module_metadata;
-        module.module_id = module_id.clone();
-        module.creator = ctx.accounts.creator.key();
-        module.price_lamports = price_lamports;
-        module.royalty_bps = royalty_bps;
-        module.total_sales = 0;
-        module.total_revenue = 0;
-        module.ipfs_hash = ipfs_hash.clone();
-        module.is_active = true;
-        module.bump = ctx.bumps.module_metadata;
+        let round = &mut ctx.accounts.round;
+        let attester = ctx.accounts.attester.key();
+        let clock = Clock::get()?;
+        require!(!round.is_finalized, ErrorCode::RoundFinalized);
+        require!(
+            round.votes.len() < round.quorum as usize,
+            ErrorCode::QuorumReached
+        );
+        require!(
+            clock.unix_timestamp <= round.opened_at + ROUND_EXPIRY_SECS,
+            ErrorCode::RoundExpired
+        );
+        require!(
+            !round.votes.iter().any(|vote| vote.attester == attester),
+            ErrorCode::DuplicateVote
+        );
 
-        emit!(ModuleRegistered {
-            module: module.key(),
-            module_id,
-            creator: module.creator,
-            price_lamports,
-            ipfs_hash,
+        round.votes.push(RoundVote {
+            attester,
+            success,
+            score_delta,
+        });
+
+        let attester_account = &mut ctx.accounts.attester_account;
+        attester_account.total_attestations += 1;
+        attester_account.last_attested_at = clock.unix_timestamp;
+
+        emit!(RoundVoteSubmitted {
+            round: round.key(),
+            attester,
+            success,
+            score_delta,
         });
 
         msg!(
-            "Module registered: {} by {}",
-            module.module_id,
-            module.creator
+            "Round vote submitted: {} ({}/{})",
+            round.key(),
+            round.votes.len(),
+            round.quorum
         );
         Ok(())
     }
 
-    pub fn purchase_module(
-        ctx: Context<PurchaseModule>,
-        referrer: Option<Pubkey>,
+    /// Settle a round that reached quorum: `success` by majority,
+    /// `score_delta` by median. The result is written to the log's
+    /// `Attestation` in the name of the settling voter, who must have voted
+    /// with the majority and whose bond answers for it. From there it goes
+    /// through the same dispute window, challenges and version tally as a
+    /// single-attester outcome.
+    pub fn settle_attestation_round(
+        ctx: Context<SettleAttestationRound>,
     ) -> Result<()> {
-        let module = &mut ctx.accounts.module_metadata;
-        let config = &ctx.accounts.platform_config;
+        let round = &mut ctx.accounts.round;
+        let attester = ctx.accounts.attester.key();
         let clock = Clock::get()?;
+        require!(!round.is_finalized, ErrorCode::RoundFinalized);
+        require!(
+            round.votes.len() >= round.quorum as usize,
+            ErrorCode::QuorumNotReached
+        );
 
-        require!(module.is_active, ErrorCode::ModuleNotActive);
+        let (success, score_delta) = round.tally();
+        require!(
+            round.votes.iter().any(
+                |vote| vote.attester == attester && vote.success == success
+            ),
+            ErrorCode::NotMajorityVoter
+        );
+        let (success, score_delta) = ctx
+            .accounts
+            .memory_log
+            .penalise_missed_reveal(clock.unix_timestamp, success, score_delta);
 
-        let price = module.price_lamports;
+        round.is_finalized = true;
+        round.success = success;
+        round.score_delta = score_delta;
+        round.finalized_at = clock.unix_timestamp;
 
-        let platform_fee = (price as u128)
-            .checked_mul(config.platform_fee_bps as u128)
-            .unwrap()
-            .checked_div(10_000)
-            .unwrap() as u64;
+        let config = &ctx.accounts.platform_config;
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.memory_log = ctx.accounts.memory_log.key();
+        attestation.attester = attester;
+        // Rounds have no outcome text; the round address stands in for it.
+        attestation.outcome_hash = round.key().to_bytes();
+        attestation.success = success;
+        attestation.score_delta = score_delta;
+        // A quorum has no single attester to rate against, so Elo treats the
+        // verdict as coming from the agent's equal.
+        attestation.attester_reputation = None;
+        attestation.applied_delta = 0;
+        attestation.timestamp = clock.unix_timestamp;
+        attestation.status = AttestationStatus::Pending;
+        attestation.dispute_deadline =
+            clock.unix_timestamp + config.dispute_window_secs;
+        attestation.bump = ctx.bumps.attestation;
 
-        let referral_fee = if referrer.is_some() {
-            (price as u128)
-                .checked_mul(config.referral_fee_bps as u128)
-                .unwrap()
-                .checked_div(10_000)
-                .unwrap() as u64
-        } else {
-            0
-        };
+        let agent = &mut ctx.accounts.agent;
+        ctx.accounts.attester_account.last_attested_at = clock.unix_timestamp;
 
-        let creator_royalty = price
-            .checked_sub(platform_fee)
-            .unwrap()
-            .checked_sub(referral_fee)
-            .unwrap();
+        emit!(AttestationRoundFinalized {
+            agent: agent.key(),
+            round: round.key(),
+            attestation: attestation.key(),
+            success,
+            score_delta,
+            votes: round.votes.len() as u8,
+            dispute_deadline: attestation.dispute_deadline,
+        });
 
-        invoke(
-            &system_instruction::transfer(
-                ctx.accounts.buyer.key,
-                &config.treasury,
-                platform_fee,
-            ),
-            &[
-                ctx.accounts.buyer.to_account_info(),
-                ctx.accounts.treasury.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-        )?;
+        if agent.refresh_reputation(config, clock.unix_timestamp) {
+            record_checkpoint(
+                agent,
+                config,
+                ctx.accounts.reputation_history.as_mut(),
+            )?;
+        }
 
-        invoke(
-            &system_instruction::transfer(
-                ctx.accounts.buyer.key,
-                &module.creator,
-                creator_royalty,
+        // With no dispute window the outcome applies immediately.
+        if config.dispute_window_secs == 0 {
+            finalize_attestation_outcome(
+                agent,
+                ctx.accounts.domain_reputation.as_mut(),
+                ctx.accounts.reputation_history.as_mut(),
+                &mut ctx.accounts.memory_log,
+                attestation,
+                config,
+                clock.unix_timestamp,
+            )?;
+        }
+
+        msg!(
+            "Attestation round settled for agent: {} (disputable until {})",
+            agent.agent_id,
+            attestation.dispute_deadline
+        );
+        Ok(())
+    }
+
+    /// Permissionless: close a round that missed quorum within
+    /// `ROUND_EXPIRY_SECS`, refunding its rent to the opener and freeing the
+    /// log to be attested again.
+    pub fn cancel_attestation_round(
+        ctx: Context<CancelAttestationRound>,
+    ) -> Result<()> {
+        let round = &ctx.accounts.round;
+        require!(
+            round.votes.len() < round.quorum as usize
+                && Clock::get()?.unix_timestamp
+                    > round.opened_at + ROUND_EXPIRY_SECS,
+            ErrorCode::RoundNotExpired
+        );
+
+        ctx.accounts.memory_log.is_attested = false;
+
+        emit!(AttestationRoundCancelled {
+            agent: round.agent,
+            memory_log: round.memory_log,
+            round: round.key(),
+            votes: round.votes.len() as u8,
+        });
+
+        msg!("Attestation round cancelled: {}", round.key());
+        Ok(())
+    }
+
+    /// Create the reputation account for one of an agent's domains. Must
+    /// exist before outcomes on logs tagged with that domain can finalise.
+    pub fn initialize_domain_reputation(
+        ctx: Context<InitializeDomainReputation>,
+        domain: String,
+    ) -> Result<()> {
+        validate_domain(&domain)?;
+        require!(!domain.is_empty(), ErrorCode::InvalidDomain);
+
+        let domain_reputation = &mut ctx.accounts.domain_reputation;
+        domain_reputation.agent = ctx.accounts.agent.key();
+        domain_reputation.domain = domain;
+        domain_reputation.reputation = 0;
+        domain_reputation.total_attestations = 0;
+        domain_reputation.success_count = 0;
+        domain_reputation.failure_count = 0;
+        domain_reputation.last_reputation_update = Clock::get()?.unix_timestamp;
+        domain_reputation.bump = ctx.bumps.domain_reputation;
+
+        msg!(
+            "Domain reputation initialized: {} / {}",
+            ctx.accounts.agent.agent_id,
+            domain_reputation.domain
+        );
+        Ok(())
+    }
+
+    /// Start checkpointing the agent's reputation. From now on every
+    /// reputation change must pass the history account. History is opt-in:
+    /// an agent that never calls this has nothing to look up, and only the
+    /// latest `MAX_REPUTATION_CHECKPOINTS` changes are retained.
+    pub fn initialize_reputation_history(
+        ctx: Context<InitializeReputationHistory>,
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        let history = &mut ctx.accounts.reputation_history;
+        history.agent = agent.key();
+        history.next = 0;
+        history.checkpoints = Vec::new();
+        history.bump = ctx.bumps.reputation_history;
+
+        agent.has_reputation_history = true;
+        record_checkpoint(agent, &ctx.accounts.platform_config, Some(history))?;
+
+        msg!("Reputation history initialized: {}", agent.agent_id);
+        Ok(())
+    }
+
+    /// Return the reputation valid at `timestamp` via return data, for
+    /// callers simulating the instruction.
+    pub fn get_reputation_at(
+        ctx: Context<GetReputationAt>,
+        timestamp: i64,
+    ) -> Result<()> {
+        let reputation = ctx
+            .accounts
+            .reputation_history
+            .reputation_at(timestamp)
+            .ok_or(ErrorCode::ReputationCheckpointNotFound)?;
+
+        set_return_data(&reputation.to_le_bytes());
+        Ok(())
+    }
+
+    /// Return the checkpoint in force at `slot`, for other programs to read
+    /// via CPI or clients to simulate. Only agents that opted into a
+    /// `ReputationHistory` can be queried, and only back to its oldest
+    /// retained checkpoint.
+    pub fn get_reputation_checkpoint_at_slot(
+        ctx: Context<GetReputationCheckpoint>,
+        slot: u64,
+    ) -> Result<ReputationCheckpoint> {
+        ctx.accounts
+            .reputation_history
+            .checkpoint_at_slot(slot)
+            .copied()
+            .ok_or(error!(ErrorCode::ReputationCheckpointNotFound))
+    }
+
+    /// Check an agent against minimum standing for other programs to gate
+    /// on via CPI. Frozen and retired agents never pass. Fails with a typed
+    /// error on the first unmet threshold; otherwise returns the agent's
+    /// standing as return data.
+    pub fn require_reputation(
+        ctx: Context<RequireReputation>,
+        requirements: ReputationRequirements,
+    ) -> Result<ReputationStanding> {
+        let agent = &ctx.accounts.agent;
+        require!(!agent.is_frozen, ErrorCode::AgentFrozen);
+        require!(!agent.is_retired, ErrorCode::AgentRetired);
+        let now = Clock::get()?.unix_timestamp;
+        let standing = ReputationStanding {
+            reputation: agent.current_reputation(
+                ctx.accounts.platform_config.decay_half_life(),
+                now,
             ),
-            &[
-                ctx.accounts.buyer.to_account_info(),
-                ctx.accounts.creator_wallet.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-        )?;
+            outcomes: agent.success_count + agent.failure_count,
+            success_rate_bps: agent.success_rate_bps(),
+            account_age_secs: now - agent.created_at,
+        };
 
-        if let Some(referrer_key) = referrer {
-            invoke(
-                &system_instruction::transfer(
-                    ctx.accounts.buyer.key,
-                    &referrer_key,
-                    referral_fee,
-                ),
-                &[
-                    ctx.accounts.buyer.to_account_info(),
-                    ctx.accounts
-                        .referrer_wallet
-                        .as_ref()
-                        .unwrap()
-                        .to_account_info(),
-                    ctx.accounts.system_program.to_account_info(),
-                ],
+        require!(
+            standing.reputation >= requirements.min_reputation,
+            ErrorCode::ReputationTooLow
+        );
+        require!(
+            standing.outcomes >= requirements.min_outcomes,
+            ErrorCode::TooFewOutcomes
+        );
+        require!(
+            standing.success_rate_bps >= requirements.min_success_rate_bps,
+            ErrorCode::SuccessRateTooLow
+        );
+        require!(
+            standing.account_age_secs >= requirements.min_account_age_secs,
+            ErrorCode::AgentTooNew
+        );
+        Ok(standing)
+    }
+
+    /// Permissionless crank that brings an agent's stored reputation up to
+    /// date with the platform's decay half-life. The decayed value is also
+    /// returned via return data for callers simulating the instruction.
+    /// A rescore that moves the score (e.g. after a switch to
+    /// `BetaBernoulli`) is checkpointed, so agents keeping a
+    /// `ReputationHistory` must pass it.
+    pub fn refresh_reputation(ctx: Context<RefreshReputation>) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        let config = &ctx.accounts.platform_config;
+        if agent.refresh_reputation(config, Clock::get()?.unix_timestamp) {
+            record_checkpoint(
+                agent,
+                config,
+                ctx.accounts.reputation_history.as_mut(),
             )?;
         }
 
-        let purchase = &mut ctx.accounts.module_purchase;
-        purchase.agent = ctx.accounts.agent.key();
-        purchase.module = module.key();
-        purchase.purchased_at = clock.unix_timestamp;
-        purchase.price_paid = price;
-        purchase.bump = ctx.bumps.module_purchase;
+        emit!(ReputationRefreshed {
+            agent: agent.key(),
+            reputation: agent.reputation,
+            timestamp: agent.last_reputation_update,
+        });
+        set_return_data(&agent.reputation.to_le_bytes());
+        Ok(())
+    }
 
-        module.total_sales += 1;
-        module.total_revenue += price;
+    /// Apply a pending attestation's `score_delta` once its dispute window
+    /// has passed without a dispute. Permissionless. Waits while the agent
+    /// is frozen and can be cranked again once it is unfrozen.
+    pub fn finalize_attestation(
+        ctx: Context<FinalizeAttestation>,
+    ) -> Result<()> {
+        let attestation = &mut ctx.accounts.attestation;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            attestation.status == AttestationStatus::Pending,
+            ErrorCode::AttestationNotPending
+        );
+        require!(
+            now > attestation.dispute_deadline,
+            ErrorCode::DisputeWindowOpen
+        );
 
-        emit!(ModulePurchased {
-            module: module.key(),
+        finalize_attestation_outcome(
+            &mut ctx.accounts.agent,
+            ctx.accounts.domain_reputation.as_mut(),
+            ctx.accounts.reputation_history.as_mut(),
+            &mut ctx.accounts.memory_log,
+            attestation,
+            &ctx.accounts.platform_config,
+            now,
+        )
+    }
+
+    /// Let the agent authority contest a pending attestation before its
+    /// dispute window closes. The arbiter settles it with `resolve_dispute`.
+    pub fn dispute_attestation(
+        ctx: Context<DisputeAttestation>,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        let attestation = &mut ctx.accounts.attestation;
+        let clock = Clock::get()?;
+        require!(
+            attestation.status == AttestationStatus::Pending,
+            ErrorCode::AttestationNotPending
+        );
+        require!(
+            clock.unix_timestamp <= attestation.dispute_deadline,
+            ErrorCode::DisputeWindowClosed
+        );
+
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.attestation = attestation.key();
+        dispute.agent = ctx.accounts.agent.key();
+        dispute.evidence_hash = evidence_hash;
+        dispute.filed_at = clock.unix_timestamp;
+        dispute.resolved_at = 0;
+        dispute.attestation_upheld = false;
+        dispute.bump = ctx.bumps.dispute;
+
+        attestation.status = AttestationStatus::Disputed;
+
+        emit!(AttestationDisputed {
+            agent: dispute.agent,
+            attestation: attestation.key(),
+            evidence_hash,
+        });
+
+        msg!("Attestation disputed: {}", attestation.key());
+        Ok(())
+    }
+
+    /// Arbiter ruling on a dispute. Upholding the attestation finalises its
+    /// reputation change, which waits until the agent is unfrozen; otherwise
+    /// the attestation is rejected.
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDispute>,
+        uphold_attestation: bool,
+    ) -> Result<()> {
+        let attestation = &mut ctx.accounts.attestation;
+        require!(
+            attestation.status == AttestationStatus::Disputed,
+            ErrorCode::AttestationNotDisputed
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.resolved_at = now;
+        dispute.attestation_upheld = uphold_attestation;
+
+        if uphold_attestation {
+            finalize_attestation_outcome(
+                &mut ctx.accounts.agent,
+                ctx.accounts.domain_reputation.as_mut(),
+                ctx.accounts.reputation_history.as_mut(),
+                &mut ctx.accounts.memory_log,
+                attestation,
+                &ctx.accounts.platform_config,
+                now,
+            )?;
+        } else {
+            attestation.status = AttestationStatus::Rejected;
+        }
+
+        emit!(DisputeResolved {
             agent: ctx.accounts.agent.key(),
-            price_paid: price,
-            platform_fee,
-            creator_royalty,
-            referral_fee,
+            attestation: attestation.key(),
+            attestation_upheld: uphold_attestation,
+            new_reputation: ctx.accounts.agent.reputation,
         });
 
         msg!(
-            "Module purchased: {} by agent {}",
-            module.module_id,
-            ctx.accounts.agent.key()
+            "Dispute resolved: {} (attestation upheld={})",
+            attestation.key(),
+            uphold_attestation
         );
         Ok(())
     }
 
-    pub fn update_module_pricing(
-        ctx: Context<UpdateModulePricing>,
-        new_price: u64,
-        new_royalty_bps: u16,
+    /// Retire the agent. The account stays as a tombstone holding its final
+    /// counters and chain head, so its own rent is never reclaimed; after
+    /// `RETENTION_PERIOD_SECS` its logs and attestations can be closed with
+    /// `close_memory_log` and `close_attestation`.
+    pub fn close_agent(ctx: Context<CloseAgent>) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        agent.is_retired = true;
+        agent.retired_at = Clock::get()?.unix_timestamp;
+
+        emit!(AgentClosed {
+            agent: agent.key(),
+            authority: agent.authority,
+            reputation: agent.reputation,
+            total_logs: agent.total_logs,
+            total_attestations: agent.total_attestations,
+            success_count: agent.success_count,
+            failure_count: agent.failure_count,
+            chain_head: agent.chain_head,
+            last_log: agent.last_log,
+            retired_at: agent.retired_at,
+        });
+
+        msg!("Agent retired: {}", agent.agent_id);
+        Ok(())
+    }
+
+    /// Platform authority: freeze a misbehaving agent. `reason` is a
+    /// platform-defined code and `evidence_hash` commits to the off-chain
+    /// evidence.
+    pub fn freeze_agent(
+        ctx: Context<ModerateAgent>,
+        reason: u16,
+        evidence_hash: [u8; 32],
     ) -> Result<()> {
-        require!(new_price >= 1_000_000, ErrorCode::PriceTooLow);
-        require!(new_royalty_bps <= 10000, ErrorCode::InvalidRoyalty);
+        let agent = &mut ctx.accounts.agent;
+        require!(!agent.is_frozen, ErrorCode::AgentFrozen);
 
-        let module = &mut ctx.accounts.module_metadata;
-        module.price_lamports = new_price;
-        module.royalty_bps = new_royalty_bps;
+        agent.is_frozen = true;
+        agent.appeal_hash = None;
+        agent.appealed_at = 0;
+        record_moderation(agent, reason, evidence_hash)?;
+
+        emit!(AgentModerated {
+            agent: agent.key(),
+            frozen: true,
+            reason,
+            evidence_hash,
+        });
+
+        msg!("Agent frozen: {} (reason={})", agent.agent_id, reason);
+        Ok(())
+    }
+
+    /// Platform authority: lift a freeze, resolving any pending appeal.
+    pub fn unfreeze_agent(
+        ctx: Context<ModerateAgent>,
+        reason: u16,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        require!(agent.is_frozen, ErrorCode::AgentNotFrozen);
+
+        agent.is_frozen = false;
+        agent.appeal_hash = None;
+        record_moderation(agent, reason, evidence_hash)?;
+
+        emit!(AgentModerated {
+            agent: agent.key(),
+            frozen: false,
+            reason,
+            evidence_hash,
+        });
+
+        msg!("Agent unfrozen: {} (reason={})", agent.agent_id, reason);
+        Ok(())
+    }
+
+    /// Appeal a freeze. `statement_hash` commits to the authority's
+    /// off-chain statement; one appeal may be pending at a time.
+    pub fn appeal_freeze(
+        ctx: Context<AppealFreeze>,
+        statement_hash: [u8; 32],
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        require!(agent.is_frozen, ErrorCode::AgentNotFrozen);
+        require!(agent.appeal_hash.is_none(), ErrorCode::AppealPending);
+
+        agent.appeal_hash = Some(statement_hash);
+        agent.appealed_at = Clock::get()?.unix_timestamp;
+
+        emit!(FreezeAppealed {
+            agent: agent.key(),
+            authority: agent.authority,
+            statement_hash,
+            moderation_reason: agent.moderation_reason,
+        });
+
+        msg!("Freeze appealed: {}", agent.agent_id);
+        Ok(())
+    }
+
+    /// Platform authority: reject the pending appeal and keep the agent
+    /// frozen. The authority may appeal again.
+    pub fn dismiss_freeze_appeal(
+        ctx: Context<ModerateAgent>,
+        reason: u16,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        require!(agent.appeal_hash.is_some(), ErrorCode::NoAppealPending);
+
+        agent.appeal_hash = None;
+        record_moderation(agent, reason, evidence_hash)?;
+
+        emit!(AgentModerated {
+            agent: agent.key(),
+            frozen: true,
+            reason,
+            evidence_hash,
+        });
+
+        msg!("Freeze appeal dismissed: {}", agent.agent_id);
+        Ok(())
+    }
+
+    /// Permissionless: close a retired agent's memory log once the
+    /// retention period has passed. An attested log's attestation and any
+    /// attestation round on it have to be closed first.
+    pub fn close_memory_log(ctx: Context<CloseMemoryLog>) -> Result<()> {
+        check_retention_elapsed(&ctx.accounts.agent)?;
+        require!(
+            ctx.accounts.attestation.data_is_empty(),
+            ErrorCode::AttestationStillOpen
+        );
+        require!(
+            ctx.accounts.round.data_is_empty(),
+            ErrorCode::AttestationRoundStillOpen
+        );
+
+        msg!("Memory log closed: {}", ctx.accounts.memory_log.key());
+        Ok(())
+    }
+
+    /// Permissionless: close a settled attestation on a retired agent's log
+    /// once the retention period has passed. The rent goes back to the
+    /// attester, who paid for the account.
+    pub fn close_attestation(ctx: Context<CloseAttestation>) -> Result<()> {
+        check_retention_elapsed(&ctx.accounts.agent)?;
+        let status = ctx.accounts.attestation.status;
+        require!(
+            status == AttestationStatus::Finalized
+                || status == AttestationStatus::Rejected,
+            ErrorCode::AttestationUnsettled
+        );
+        require!(
+            ctx.accounts.challenge.data_is_empty(),
+            ErrorCode::ChallengeStillOpen
+        );
+
+        msg!("Attestation closed: {}", ctx.accounts.attestation.key());
+        Ok(())
+    }
+
+    /// Permissionless: close a retired agent's decision batch once the
+    /// retention period has passed.
+    pub fn close_decision_batch(
+        ctx: Context<CloseDecisionBatch>,
+    ) -> Result<()> {
+        check_retention_elapsed(&ctx.accounts.agent)?;
+
+        msg!("Decision batch closed: {}", ctx.accounts.batch.key());
+        Ok(())
+    }
+
+    /// Permissionless: close an attestation round on a retired agent's log
+    /// once the retention period has passed, refunding the opener.
+    pub fn close_attestation_round(
+        ctx: Context<CloseAttestationRound>,
+    ) -> Result<()> {
+        check_retention_elapsed(&ctx.accounts.agent)?;
+
+        msg!("Attestation round closed: {}", ctx.accounts.round.key());
+        Ok(())
+    }
+
+    /// Permissionless: close a resolved dispute against a retired agent
+    /// once the retention period has passed.
+    pub fn close_dispute(ctx: Context<CloseDispute>) -> Result<()> {
+        check_retention_elapsed(&ctx.accounts.agent)?;
+        require!(
+            ctx.accounts.dispute.resolved_at != 0,
+            ErrorCode::DisputeStillOpen
+        );
+
+        msg!("Dispute closed: {}", ctx.accounts.dispute.key());
+        Ok(())
+    }
+
+    /// Permissionless: drop a challenge against a retired agent's
+    /// attestation that was never resolved within the retention period.
+    /// The challenger gets the escrow and rent back and the attester's bond
+    /// is released from it.
+    pub fn close_challenge(ctx: Context<CloseChallenge>) -> Result<()> {
+        check_retention_elapsed(&ctx.accounts.agent)?;
+
+        let attester_account = &mut ctx.accounts.attester_account;
+        attester_account.open_challenges =
+            attester_account.open_challenges.saturating_sub(1);
+
+        msg!("Challenge closed: {}", ctx.accounts.challenge.key());
+        Ok(())
+    }
+
+    /// Permissionless: close a retired agent's delegate grant once the
+    /// retention period has passed.
+    pub fn close_delegate(ctx: Context<CloseDelegate>) -> Result<()> {
+        check_retention_elapsed(&ctx.accounts.agent)?;
+
+        msg!("Delegate closed: {}", ctx.accounts.delegate.key());
+        Ok(())
+    }
+
+    /// Permissionless: close a retired agent's domain reputation once the
+    /// retention period has passed.
+    pub fn close_domain_reputation(
+        ctx: Context<CloseDomainReputation>,
+    ) -> Result<()> {
+        check_retention_elapsed(&ctx.accounts.agent)?;
 
         msg!(
-            "Module pricing updated: {} (price={}, royalty={}bps)",
-            module.module_id,
-            new_price,
-            new_royalty_bps
+            "Domain reputation closed: {}",
+            ctx.accounts.domain_reputation.key()
         );
         Ok(())
     }
 
-    pub fn deactivate_module(ctx: Context<DeactivateModule>) -> Result<()> {
-        let module = &mut ctx.accounts.module_metadata;
-        module.is_active = false;
-        msg!("Module deactivated: {}", module.module_id);
+    /// Permissionless: close a retired agent's reputation history once the
+    /// retention period has passed. Later reputation changes stop needing
+    /// a checkpoint.
+    pub fn close_reputation_history(
+        ctx: Context<CloseReputationHistory>,
+    ) -> Result<()> {
+        check_retention_elapsed(&ctx.accounts.agent)?;
+        ctx.accounts.agent.has_reputation_history = false;
+
+        msg!(
+            "Reputation history closed: {}",
+            ctx.accounts.reputation_history.key()
+        );
         Ok(())
     }
-}
 
-// ============================================================================
-// Account Structures
-// ============================================================================
+    /// Permissionless: close a retired agent's version record once the
+    /// retention period has passed.
+    pub fn close_agent_version(ctx: Context<CloseAgentVersion>) -> Result<()> {
+        check_retention_elapsed(&ctx.accounts.agent)?;
 
-#[account]
-pub struct AgentAccount {
-    pub agent_id: String,
-    pub authority: Pubkey,
-    pub reputation: u64,
-    pub total_logs: u64,
-    pub total_attestations: u64,
-    pub bump: u8,
-}
+        msg!("Agent version closed: {}", ctx.accounts.agent_version.key());
+        Ok(())
+    }
 
-#[account]
-pub struct MemoryLog {
-    pub agent: Pubkey,
-    pub input_hash: [u8; 32],
-    pub logic_hash: [u8; 32],
-    pub merkle_root: [u8; 32],
-    pub timestamp: i64,
-    pub is_attested: bool,
-    pub bump: u8,
+    /// Permissionless: close a retired agent's module purchase receipt once
+    /// the retention period has passed.
+    pub fn close_module_purchase(
+        ctx: Context<CloseModulePurchase>,
+    ) -> Result<()> {
+        check_retention_elapsed(&ctx.accounts.agent)?;
+
+        msg!(
+            "Module purchase closed: {}",
+            ctx.accounts.module_purchase.key()
+        );
+        Ok(())
+    }
+
+    /// Replace the agent's profile. The account is resized to fit, with
+    /// the authority paying for growth and refunded for shrinkage.
+    pub fn update_agent_profile(
+        ctx: Context<UpdateAgentProfile>,
+        profile: AgentProfile,
+    ) -> Result<()> {
+        profile.validate()?;
+
+        let agent = &mut ctx.accounts.agent;
+        agent.profile = profile;
+
+        emit!(AgentProfileUpdated {
+            agent: agent.key(),
+            metadata_uri: agent.profile.metadata_uri.clone(),
+            metadata_hash: agent.profile.metadata_hash,
+            capabilities: agent.profile.capabilities,
+        });
+
+        msg!("Agent profile updated: {}", agent.agent_id);
+        Ok(())
+    }
+
+    /// Grant `session_key` a session to log decisions on the agent's behalf.
+    pub fn grant_delegate(
+        ctx: Context<GrantDelegate>,
+        session_key: Pubkey,
+        expires_at: i64,
+        max_logs: u64,
+        can_purchase: bool,
+    ) -> Result<()> {
+        require!(
+            expires_at > Clock::get()?.unix_timestamp && max_logs > 0,
+            ErrorCode::InvalidDelegateGrant
+        );
+
+        let grant = &mut ctx.accounts.delegate;
+        grant.agent = ctx.accounts.agent.key();
+        grant.delegate = session_key;
+        grant.can_purchase = can_purchase;
+        grant.expires_at = expires_at;
+        grant.max_logs = max_logs;
+        grant.logs_used = 0;
+        grant.authority_epoch = ctx.accounts.agent.authority_epoch;
+        grant.bump = ctx.bumps.delegate;
+
+        emit!(DelegateGranted {
+            agent: grant.agent,
+            delegate: session_key,
+            expires_at,
+            max_logs,
+            can_purchase,
+        });
+
+        msg!(
+            "Delegate granted: {} -> {}",
+            ctx.accounts.agent.agent_id,
+            session_key
+        );
+        Ok(())
+    }
+
+    /// Revoke a session key before it expires.
+    pub fn revoke_delegate(ctx: Context<RevokeDelegate>) -> Result<()> {
+        emit!(DelegateRevoked {
+            agent: ctx.accounts.agent.key(),
+            delegate: ctx.accounts.delegate.delegate,
+        });
+
+        msg!("Delegate revoked: {}", ctx.accounts.delegate.delegate);
+        Ok(())
+    }
+
+    /// First step of handing the agent to a new authority, which must then
+    /// call `accept_authority_transfer`. Proposing again replaces the
+    /// pending authority.
+    pub fn propose_authority_transfer(
+        ctx: Context<UpdateAgentAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        agent.pending_authority = Some(new_authority);
+
+        emit!(AuthorityTransferProposed {
+            agent: agent.key(),
+            authority: agent.authority,
+            pending_authority: new_authority,
+        });
+
+        msg!(
+            "Authority transfer proposed: {} -> {}",
+            agent.agent_id,
+            new_authority
+        );
+        Ok(())
+    }
+
+    /// Second step of an authority transfer, signed by the proposed key.
+    pub fn accept_authority_transfer(
+        ctx: Context<AcceptAuthorityTransfer>,
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        let previous = agent.authority;
+        agent.authority = ctx.accounts.new_authority.key();
+        agent.pending_authority = None;
+        agent.authority_epoch += 1;
+
+        emit!(AuthorityTransferred {
+            agent: agent.key(),
+            previous_authority: previous,
+            new_authority: agent.authority,
+            via_recovery: false,
+        });
+
+        msg!(
+            "Authority transferred: {} -> {}",
+            agent.agent_id,
+            agent.authority
+        );
+        Ok(())
+    }
+
+    /// Designate (or clear) the recovery key that can rotate the authority
+    /// with `recover_authority`. Once a recovery key is set, replacing or
+    /// clearing it also needs that key's signature, so a leaked authority
+    /// key cannot disable recovery.
+    pub fn set_recovery_authority(
+        ctx: Context<SetRecoveryAuthority>,
+        recovery_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        if let Some(current) = agent.recovery_authority {
+            require!(
+                ctx.accounts
+                    .recovery_authority
+                    .as_ref()
+                    .is_some_and(|signer| signer.key() == current),
+                ErrorCode::NotRecoveryAuthority
+            );
+        }
+        agent.recovery_authority = recovery_authority;
+
+        emit!(RecoveryAuthorityChanged {
+            agent: agent.key(),
+            recovery_authority,
+        });
+
+        msg!("Recovery authority updated: {}", agent.agent_id);
+        Ok(())
+    }
+
+    /// Emergency rotation signed by the recovery key: replaces the
+    /// authority immediately, drops any pending transfer and invalidates
+    /// every existing `Delegate` grant.
+    pub fn recover_authority(
+        ctx: Context<RecoverAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        let previous = agent.authority;
+        agent.authority = new_authority;
+        agent.pending_authority = None;
+        agent.authority_epoch += 1;
+
+        emit!(AuthorityTransferred {
+            agent: agent.key(),
+            previous_authority: previous,
+            new_authority,
+            via_recovery: true,
+        });
+
+        msg!(
+            "Authority recovered: {} -> {}",
+            agent.agent_id,
+            new_authority
+        );
+        Ok(())
+    }
+
+    /// Endorse another agent, adding the endorser's current reputation to
+    /// the endorsee's `endorsement_score`. Agents without positive
+    /// reputation have nothing to lend and cannot endorse.
+    pub fn endorse_agent(ctx: Context<EndorseAgent>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let weight = ctx.accounts.endorser.current_reputation(
+            ctx.accounts.platform_config.decay_half_life(),
+            now,
+        );
+        require!(weight > 0, ErrorCode::EndorserReputationTooLow);
+        let weight = weight as u64;
+
+        let endorsement = &mut ctx.accounts.endorsement;
+        endorsement.endorser = ctx.accounts.endorser.key();
+        endorsement.endorsee = ctx.accounts.endorsee.key();
+        endorsement.weight = weight;
+        endorsement.created_at = now;
+        endorsement.bump = ctx.bumps.endorsement;
+
+        let endorsee = &mut ctx.accounts.endorsee;
+        endorsee.endorsement_score = endorsee
+            .endorsement_score
+            .checked_add(weight)
+            .ok_or(ErrorCode::ReputationOverflow)?;
+        endorsee.endorsement_count += 1;
+
+        emit!(AgentEndorsed {
+            endorser: endorsement.endorser,
+            endorsee: endorsement.endorsee,
+            weight,
+            endorsement_score: endorsee.endorsement_score,
+        });
+
+        msg!("Agent endorsed: {} (weight={})", endorsee.agent_id, weight);
+        Ok(())
+    }
+
+    /// Permissionless: re-weigh an endorsement by the endorser's current
+    /// reputation, so an endorser whose standing fell no longer props the
+    /// endorsee up at its old weight. A frozen or retired endorser, or one
+    /// without positive reputation, counts for nothing.
+    pub fn refresh_endorsement(ctx: Context<RefreshEndorsement>) -> Result<()> {
+        let endorser = &ctx.accounts.endorser;
+        let weight = if endorser.is_frozen || endorser.is_retired {
+            0
+        } else {
+            endorser
+                .current_reputation(
+                    ctx.accounts.platform_config.decay_half_life(),
+                    Clock::get()?.unix_timestamp,
+                )
+                .max(0) as u64
+        };
+
+        let endorsement = &mut ctx.accounts.endorsement;
+        let endorsee = &mut ctx.accounts.endorsee;
+        endorsee.endorsement_score = endorsee
+            .endorsement_score
+            .saturating_sub(endorsement.weight)
+            .checked_add(weight)
+            .ok_or(ErrorCode::ReputationOverflow)?;
+        endorsement.weight = weight;
+
+        emit!(EndorsementRefreshed {
+            endorser: endorsement.endorser,
+            endorsee: endorsement.endorsee,
+            weight,
+            endorsement_score: endorsee.endorsement_score,
+        });
+
+        msg!(
+            "Endorsement refreshed: {} (weight={})",
+            endorsee.agent_id,
+            weight
+        );
+        Ok(())
+    }
+
+    /// Withdraw an endorsement, removing its weight from the endorsee.
+    pub fn revoke_endorsement(ctx: Context<RevokeEndorsement>) -> Result<()> {
+        let weight = ctx.accounts.endorsement.weight;
+        let endorsee = &mut ctx.accounts.endorsee;
+        endorsee.endorsement_score =
+            endorsee.endorsement_score.saturating_sub(weight);
+        endorsee.endorsement_count =
+            endorsee.endorsement_count.saturating_sub(1);
+
+        emit!(EndorsementRevoked {
+            endorser: ctx.accounts.endorser.key(),
+            endorsee: endorsee.key(),
+            weight,
+            endorsement_score: endorsee.endorsement_score,
+        });
+
+        msg!("Endorsement revoked: {}", endorsee.agent_id);
+        Ok(())
+    }
+
+    /// Create an organisation with the signer as its only admin. Agents
+    /// join it at `initialize_agent`.
+    pub fn initialize_organization(
+        ctx: Context<InitializeOrganization>,
+        org_id: String,
+    ) -> Result<()> {
+        validate_agent_id(&org_id)?;
+
+        let organization = &mut ctx.accounts.organization;
+        organization.org_id = org_id;
+        organization.admins = vec![ctx.accounts.admin.key()];
+        organization.agent_count = 0;
+        organization.total_reputation = 0;
+        organization.total_logs = 0;
+        organization.total_spent = 0;
+        organization.member_spend_limit = 0;
+        organization.created_at = Clock::get()?.unix_timestamp;
+        organization.bump = ctx.bumps.organization;
+
+        msg!("Organization initialized: {}", organization.org_id);
+        Ok(())
+    }
+
+    /// Replace the organisation's admin set.
+    pub fn set_organization_admins(
+        ctx: Context<UpdateOrganization>,
+        admins: Vec<Pubkey>,
+    ) -> Result<()> {
+        require!(
+            !admins.is_empty() && admins.len() <= MAX_ORGANIZATION_ADMINS,
+            ErrorCode::InvalidOrganizationAdmins
+        );
+
+        let organization = &mut ctx.accounts.organization;
+        organization.admins = admins;
+
+        msg!(
+            "Organization admins updated: {} ({} admins)",
+            organization.org_id,
+            organization.admins.len()
+        );
+        Ok(())
+    }
+
+    /// Cap how much each member agent may spend from the shared budget
+    /// (0 removes the cap).
+    pub fn set_member_spend_limit(
+        ctx: Context<UpdateOrganization>,
+        member_spend_limit: u64,
+    ) -> Result<()> {
+        let organization = &mut ctx.accounts.organization;
+        organization.member_spend_limit = member_spend_limit;
+
+        msg!(
+            "Organization member spend limit updated: {} ({} lamports)",
+            organization.org_id,
+            member_spend_limit
+        );
+        Ok(())
+    }
+
+    /// Take an agent out of its organisation. Its reported stats are
+    /// removed from the totals and it loses access to the shared budget.
+    /// Agents only join at `initialize_agent`, so leaving is permanent.
+    pub fn leave_organization(ctx: Context<LeaveOrganization>) -> Result<()> {
+        detach_from_organization(
+            &mut ctx.accounts.organization,
+            &mut ctx.accounts.agent,
+        )
+    }
+
+    /// Admin-only: remove a member agent from the organisation, as
+    /// `leave_organization` does.
+    pub fn remove_from_organization(
+        ctx: Context<RemoveFromOrganization>,
+    ) -> Result<()> {
+        detach_from_organization(
+            &mut ctx.accounts.organization,
+            &mut ctx.accounts.agent,
+        )
+    }
+
+    /// Add lamports to the organisation's shared module budget. Anyone may
+    /// fund an organisation.
+    pub fn fund_organization(
+        ctx: Context<FundOrganization>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidBudgetAmount);
+
+        invoke(
+            &system_instruction::transfer(
+                ctx.accounts.funder.key,
+                &ctx.accounts.organization.key(),
+                amount,
+            ),
+            &[
+                ctx.accounts.funder.to_account_info(),
+                ctx.accounts.organization.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        emit!(OrganizationBudgetChanged {
+            organization: ctx.accounts.organization.key(),
+            budget: organization_budget(
+                &ctx.accounts.organization.to_account_info()
+            )?,
+        });
+
+        msg!(
+            "Organization funded: {} (+{} lamports)",
+            ctx.accounts.organization.org_id,
+            amount
+        );
+        Ok(())
+    }
+
+    /// Return unspent budget to the signing admin.
+    pub fn withdraw_organization_budget(
+        ctx: Context<UpdateOrganization>,
+        amount: u64,
+    ) -> Result<()> {
+        let organization_info = ctx.accounts.organization.to_account_info();
+        require!(
+            amount > 0 && amount <= organization_budget(&organization_info)?,
+            ErrorCode::InvalidBudgetAmount
+        );
+
+        move_lamports(
+            &organization_info,
+            &ctx.accounts.admin.to_account_info(),
+            amount,
+        )?;
+
+        emit!(OrganizationBudgetChanged {
+            organization: organization_info.key(),
+            budget: organization_budget(&organization_info)?,
+        });
+
+        msg!(
+            "Organization budget withdrawn: {} (-{} lamports)",
+            ctx.accounts.organization.org_id,
+            amount
+        );
+        Ok(())
+    }
+
+    /// Start a new model/prompt version for the agent. Versions are
+    /// numbered from 1 and every later log is attributed to this one.
+    pub fn activate_agent_version(
+        ctx: Context<ActivateAgentVersion>,
+        version: u32,
+        model_hash: [u8; 32],
+        config_hash: [u8; 32],
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent;
+        require!(
+            version == agent.active_version + 1,
+            ErrorCode::InvalidAgentVersion
+        );
+
+        let agent_version = &mut ctx.accounts.agent_version;
+        agent_version.agent = agent.key();
+        agent_version.version = version;
+        agent_version.model_hash = model_hash;
+        agent_version.config_hash = config_hash;
+        agent_version.activated_at = Clock::get()?.unix_timestamp;
+        agent_version.success_count = 0;
+        agent_version.failure_count = 0;
+        agent_version.reputation = 0;
+        agent_version.bump = ctx.bumps.agent_version;
+
+        agent.active_version = version;
+
+        emit!(AgentVersionActivated {
+            agent: agent.key(),
+            version,
+            model_hash,
+            config_hash,
+            activated_at: agent_version.activated_at,
+        });
+
+        msg!("Agent version activated: {} v{}", agent.agent_id, version);
+        Ok(())
+    }
+
+    /// Permissionless: credit a finalised outcome to the version that logged
+    /// the decision. Waits out the challenge window and for any open
+    /// challenge to be resolved, so the outcome can no longer be reversed.
+    pub fn tally_version_outcome(
+        ctx: Context<TallyVersionOutcome>,
+    ) -> Result<()> {
+        let attestation = &ctx.accounts.attestation;
+        let memory_log = &mut ctx.accounts.memory_log;
+        require!(
+            attestation.status == AttestationStatus::Finalized,
+            ErrorCode::AttestationNotFinalized
+        );
+        require!(
+            Clock::get()?.unix_timestamp
+                > attestation.timestamp + CHALLENGE_WINDOW_SECS,
+            ErrorCode::ChallengeWindowOpen
+        );
+        require!(
+            ctx.accounts.challenge.data_is_empty(),
+            ErrorCode::ChallengeStillOpen
+        );
+        require!(
+            !memory_log.version_tallied,
+            ErrorCode::VersionAlreadyTallied
+        );
+
+        let agent_version = &mut ctx.accounts.agent_version;
+        if attestation.success {
+            agent_version.success_count += 1;
+        } else {
+            agent_version.failure_count += 1;
+        }
+        agent_version.reputation = agent_version
+            .reputation
+            .checked_add(attestation.applied_delta)
+            .ok_or(ErrorCode::ReputationOverflow)?;
+        memory_log.version_tallied = true;
+
+        emit!(AgentVersionOutcomeTallied {
+            agent: agent_version.agent,
+            version: agent_version.version,
+            memory_log: memory_log.key(),
+            success: attestation.success,
+            reputation: agent_version.reputation,
+        });
+
+        msg!(
+            "Version outcome tallied: v{} (reputation={})",
+            agent_version.version,
+            agent_version.reputation
+        );
+        Ok(())
+    }
+
+    /// Permissionless: fold a member agent's current reputation and log
+    /// count into the organisation totals and publish them.
+    pub fn sync_organization_stats(
+        ctx: Context<SyncOrganizationStats>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let agent = &mut ctx.accounts.agent;
+        let organization = &mut ctx.accounts.organization;
+
+        let reputation = agent.current_reputation(
+            ctx.accounts.platform_config.decay_half_life(),
+            now,
+        );
+        let change = reputation
+            .checked_sub(agent.org_reported_reputation)
+            .ok_or(ErrorCode::ReputationOverflow)?;
+        organization.total_reputation = organization
+            .total_reputation
+            .checked_add(change)
+            .ok_or(ErrorCode::ReputationOverflow)?;
+        organization.total_logs += agent.total_logs - agent.org_reported_logs;
+        agent.org_reported_reputation = reputation;
+        agent.org_reported_logs = agent.total_logs;
+
+        emit!(OrganizationStatsUpdated {
+            organization: organization.key(),
+            agent_count: organization.agent_count,
+            total_reputation: organization.total_reputation,
+            total_logs: organization.total_logs,
+            total_spent: organization.total_spent,
+        });
+
+        msg!(
+            "Organization stats synced: {} (reputation={}, logs={})",
+            organization.org_id,
+            organization.total_reputation,
+            organization.total_logs
+        );
+        Ok(())
+    }
+
+    pub fn initialize_platform(
+        ctx: Context<InitializePlatform>,
+        treasury: Pubkey,
+        platform_fee_bps: u16,
+        referral_fee_bps: u16,
+    ) -> Result<()> {
+        require!(platform_fee_bps <= 1000, ErrorCode::FeeTooHigh);
+        require!(referral_fee_bps <= 1000, ErrorCode::FeeTooHigh);
+
+        let config = &mut ctx.accounts.platform_config;
+        config.authority = ctx.accounts.authority.key();
+        config.treasury = treasury;
+        config.platform_fee_bps = platform_fee_bps;
+        config.referral_fee_bps = referral_fee_bps;
+        config.arbiter = ctx.accounts.authority.key();
+        config.dispute_window_secs = DEFAULT_DISPUTE_WINDOW_SECS;
+        config.reputation_half_life_secs = 0;
+        config.scoring_model = ScoringModel::Raw;
+        config.reserved_names = Vec::new();
+        config.name_reservation_fee = DEFAULT_NAME_RESERVATION_FEE;
+        config.bump = ctx.bumps.platform_config;
+
+        msg!(
+            "Platform initialized: treasury={}, fee={}bps",
+            treasury,
+            platform_fee_bps
+        );
+        Ok(())
+    }
+
+    /// Appoint the dispute arbiter and set how long attestations stay
+    /// disputable (0 applies outcomes immediately).
+    pub fn set_dispute_config(
+        ctx: Context<UpdatePlatformConfig>,
+        arbiter: Pubkey,
+        dispute_window_secs: i64,
+    ) -> Result<()> {
+        require!(
+            (0..=MAX_DISPUTE_WINDOW_SECS).contains(&dispute_window_secs),
+            ErrorCode::InvalidDisputeWindow
+        );
+
+        let config = &mut ctx.accounts.platform_config;
+        config.arbiter = arbiter;
+        config.dispute_window_secs = dispute_window_secs;
+
+        msg!(
+            "Dispute config updated: arbiter={}, window={}s",
+            arbiter,
+            dispute_window_secs
+        );
+        Ok(())
+    }
+
+    /// Upgrade the `PlatformConfig` written before dispute settings existed
+    /// to the current layout, filling new settings with their defaults.
+    pub fn migrate_platform_config(
+        ctx: Context<MigratePlatformConfig>,
+    ) -> Result<()> {
+        let config_info = ctx.accounts.platform_config.to_account_info();
+
+        let legacy = {
+            let data = config_info.try_borrow_data()?;
+            require!(
+                data.len() == LegacyPlatformConfig::LEN
+                    && data[..8] == PlatformConfig::DISCRIMINATOR,
+                ErrorCode::NotLegacyPlatformConfig
+            );
+            LegacyPlatformConfig::deserialize(&mut &data[8..])?
+        };
+        require_keys_eq!(
+            legacy.authority,
+            ctx.accounts.authority.key(),
+            ErrorCode::NotLegacyPlatformConfig
+        );
+
+        resize_account(
+            &config_info,
+            PlatformConfig::LEN,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        let migrated = PlatformConfig {
+            authority: legacy.authority,
+            treasury: legacy.treasury,
+            platform_fee_bps: legacy.platform_fee_bps,
+            referral_fee_bps: legacy.referral_fee_bps,
+            arbiter: legacy.authority,
+            dispute_window_secs: DEFAULT_DISPUTE_WINDOW_SECS,
+            reputation_half_life_secs: 0,
+            scoring_model: ScoringModel::Raw,
+            reserved_names: Vec::new(),
+            name_reservation_fee: DEFAULT_NAME_RESERVATION_FEE,
+            bump: legacy.bump,
+        };
+        let mut data = config_info.try_borrow_mut_data()?;
+        migrated.try_serialize(&mut &mut data[..])?;
+
+        msg!("Legacy platform config migrated");
+        Ok(())
+    }
+
+    /// Set the reputation half-life in seconds (0 disables decay).
+    pub fn set_reputation_decay(
+        ctx: Context<UpdatePlatformConfig>,
+        half_life_secs: i64,
+    ) -> Result<()> {
+        require!(half_life_secs >= 0, ErrorCode::InvalidHalfLife);
+
+        ctx.accounts.platform_config.reputation_half_life_secs = half_life_secs;
+
+        msg!("Reputation half-life updated: {}s", half_life_secs);
+        Ok(())
+    }
+
+    /// Replace the list of agent IDs only the platform authority may
+    /// register.
+    pub fn set_reserved_names(
+        ctx: Context<UpdatePlatformConfig>,
+        reserved_names: Vec<String>,
+    ) -> Result<()> {
+        require!(
+            reserved_names.len() <= MAX_RESERVED_NAMES,
+            ErrorCode::TooManyReservedNames
+        );
+        for name in &reserved_names {
+            validate_agent_id(name)?;
+        }
+
+        ctx.accounts.platform_config.reserved_names = reserved_names;
+
+        msg!(
+            "Reserved names updated: {}",
+            ctx.accounts.platform_config.reserved_names.len()
+        );
+        Ok(())
+    }
+
+    pub fn set_name_reservation_fee(
+        ctx: Context<UpdatePlatformConfig>,
+        fee_lamports: u64,
+    ) -> Result<()> {
+        ctx.accounts.platform_config.name_reservation_fee = fee_lamports;
+
+        msg!("Name reservation fee updated: {} lamports", fee_lamports);
+        Ok(())
+    }
+
+    /// Pay the platform fee to hold `name` for
+    /// `NAME_RESERVATION_PERIOD_SECS`, so only the owner can register an
+    /// agent under it.
+    pub fn reserve_agent_name(
+        ctx: Context<ReserveAgentName>,
+        name: String,
+    ) -> Result<()> {
+        validate_agent_id(&name)?;
+        let config = &ctx.accounts.platform_config;
+        require!(
+            !config.reserved_names.contains(&name),
+            ErrorCode::NameUnavailable
+        );
+        require!(
+            ctx.accounts.agent.data_is_empty(),
+            ErrorCode::NameUnavailable
+        );
+
+        pay_name_reservation_fee(
+            config.name_reservation_fee,
+            &ctx.accounts.owner,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+
+        let reservation = &mut ctx.accounts.name_reservation;
+        reservation.name = name;
+        reservation.owner = ctx.accounts.owner.key();
+        reservation.expires_at =
+            Clock::get()?.unix_timestamp + NAME_RESERVATION_PERIOD_SECS;
+        reservation.bump = ctx.bumps.name_reservation;
+
+        emit!(AgentNameReserved {
+            name: reservation.name.clone(),
+            owner: reservation.owner,
+            expires_at: reservation.expires_at,
+        });
+
+        msg!("Agent name reserved: {}", reservation.name);
+        Ok(())
+    }
+
+    /// Extend a reservation by another period for the current fee.
+    pub fn renew_name_reservation(
+        ctx: Context<RenewNameReservation>,
+    ) -> Result<()> {
+        pay_name_reservation_fee(
+            ctx.accounts.platform_config.name_reservation_fee,
+            &ctx.accounts.owner,
+            &ctx.accounts.treasury,
+            &ctx.accounts.system_program,
+        )?;
+
+        let reservation = &mut ctx.accounts.name_reservation;
+        reservation.expires_at =
+            reservation.expires_at.max(Clock::get()?.unix_timestamp)
+                + NAME_RESERVATION_PERIOD_SECS;
+
+        emit!(AgentNameReserved {
+            name: reservation.name.clone(),
+            owner: reservation.owner,
+            expires_at: reservation.expires_at,
+        });
+
+        msg!("Agent name reservation renewed: {}", reservation.name);
+        Ok(())
+    }
+
+    /// Permissionless: close an expired reservation, returning its rent to
+    /// the former owner and freeing the name to be reserved again.
+    pub fn release_name_reservation(
+        ctx: Context<ReleaseNameReservation>,
+    ) -> Result<()> {
+        let reservation = &ctx.accounts.name_reservation;
+        require!(
+            reservation.expires_at <= Clock::get()?.unix_timestamp,
+            ErrorCode::NameReservationActive
+        );
+
+        emit!(AgentNameReleased {
+            name: reservation.name.clone(),
+            owner: reservation.owner,
+        });
+
+        msg!("Agent name reservation released: {}", reservation.name);
+        Ok(())
+    }
+
+    /// Choose how attested outcomes are scored. Applies to outcomes
+    /// finalised from now on. Switching to `BetaBernoulli` makes each
+    /// agent's reputation jump to its posterior mean (in basis points) the
+    /// next time it is refreshed or scored, since a running score has no
+    /// meaning as a probability; `refresh_reputation` rescores an agent
+    /// straight away. Switching away keeps that value as the starting score.
+    pub fn set_scoring_model(
+        ctx: Context<UpdatePlatformConfig>,
+        scoring_model: ScoringModel,
+    ) -> Result<()> {
+        scoring_model.validate()?;
+
+        ctx.accounts.platform_config.scoring_model = scoring_model;
+
+        emit!(ScoringModelUpdated { scoring_model });
+        msg!("Scoring model updated: {:?}", scoring_model);
+        Ok(())
+    }
+
+    /// Add `attester` to the platform's attester registry. Only registered,
+    /// active attesters may call `attest_outcome`.
+    pub fn register_attester(
+        ctx: Context<RegisterAttester>,
+        attester: Pubkey,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let account = &mut ctx.accounts.attester_account;
+        account.attester = attester;
+        account.agent = None;
+        account.is_active = true;
+        account.registered_at = clock.unix_timestamp;
+        account.total_attestations = 0;
+        account.bond_lamports = 0;
+        account.open_challenges = 0;
+        account.last_attested_at = 0;
+        account.bump = ctx.bumps.attester_account;
+
+        ctx.accounts.attester_vault.bump = ctx.bumps.attester_vault;
+
+        emit!(AttesterStatusChanged {
+            attester,
+            is_active: true,
+        });
+
+        msg!("Attester registered: {}", attester);
+        Ok(())
+    }
+
+    /// Link the attester to the agent it runs. Under `ScoringModel::Elo`
+    /// its attestations are rated against that agent's reputation. The link
+    /// is permanent, so an attester with several agents cannot pick a
+    /// favourable rating per attestation.
+    pub fn link_attester_agent(ctx: Context<LinkAttesterAgent>) -> Result<()> {
+        let attester_account = &mut ctx.accounts.attester_account;
+        attester_account.agent = Some(ctx.accounts.agent.key());
+
+        msg!(
+            "Attester {} linked to agent {}",
+            attester_account.attester,
+            ctx.accounts.agent.agent_id
+        );
+        Ok(())
+    }
+
+    /// Suspend or reinstate a registered attester.
+    pub fn set_attester_active(
+        ctx: Context<SetAttesterActive>,
+        is_active: bool,
+    ) -> Result<()> {
+        let account = &mut ctx.accounts.attester_account;
+        account.is_active = is_active;
+
+        emit!(AttesterStatusChanged {
+            attester: account.attester,
+            is_active,
+        });
+
+        msg!(
+            "Attester {} active: {}",
+            account.attester,
+            account.is_active
+        );
+        Ok(())
+    }
+
+    /// Restrict which registered attesters may attest this agent's logs.
+    pub fn initialize_attester_allowlist(
+        ctx: Context<InitializeAttesterAllowlist>,
+        attesters: Vec<Pubkey>,
+    ) -> Result<()> {
+        require!(
+            attesters.len() <= MAX_ALLOWED_ATTESTERS,
+            ErrorCode::TooManyAllowedAttesters
+        );
+
+        let allowlist = &mut ctx.accounts.attester_allowlist;
+        allowlist.agent = ctx.accounts.agent.key();
+        allowlist.attesters = attesters;
+        allowlist.bump = ctx.bumps.attester_allowlist;
+
+        emit!(AttesterAllowlistUpdated {
+            agent: allowlist.agent,
+            attesters: allowlist.attesters.clone(),
+        });
+        Ok(())
+    }
+
+    pub fn update_attester_allowlist(
+        ctx: Context<UpdateAttesterAllowlist>,
+        attesters: Vec<Pubkey>,
+    ) -> Result<()> {
+        require!(
+            attesters.len() <= MAX_ALLOWED_ATTESTERS,
+            ErrorCode::TooManyAllowedAttesters
+        );
+
+        let allowlist = &mut ctx.accounts.attester_allowlist;
+        allowlist.attesters = attesters;
+
+        emit!(AttesterAllowlistUpdated {
+            agent: allowlist.agent,
+            attesters: allowlist.attesters.clone(),
+        });
+        Ok(())
+    }
+
+    /// Drop the allowlist, so any registered attester may attest again.
+    pub fn close_attester_allowlist(
+        ctx: Context<CloseAttesterAllowlist>,
+    ) -> Result<()> {
+        emit!(AttesterAllowlistUpdated {
+            agent: ctx.accounts.agent.key(),
+            attesters: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Lock SOL in the attester's vault. `attest_outcome` requires at least
+    /// `MIN_ATTESTER_BOND` lamports to be bonded.
+    pub fn deposit_attester_bond(
+        ctx: Context<DepositAttesterBond>,
+        amount: u64,
+    ) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidBondAmount);
+
+        invoke(
+            &system_instruction::transfer(
+                ctx.accounts.attester.key,
+                &ctx.accounts.attester_vault.key(),
+                amount,
+            ),
+            &[
+                ctx.accounts.attester.to_account_info(),
+                ctx.accounts.attester_vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        let account = &mut ctx.accounts.attester_account;
+        account.bond_lamports = account
+            .bond_lamports
+            .checked_add(amount)
+            .ok_or(ErrorCode::InvalidBondAmount)?;
+
+        emit!(AttesterBondChanged {
+            attester: account.attester,
+            bond_lamports: account.bond_lamports,
+        });
+
+        msg!(
+            "Attester bond deposited: {} (bond={})",
+            account.attester,
+            account.bond_lamports
+        );
+        Ok(())
+    }
+
+    /// Release bonded SOL once the attester has no open challenges and its
+    /// latest attestation is past the challenge window.
+    pub fn withdraw_attester_bond(
+        ctx: Context<WithdrawAttesterBond>,
+        amount: u64,
+    ) -> Result<()> {
+        let account = &mut ctx.accounts.attester_account;
+        let clock = Clock::get()?;
+        require!(
+            amount > 0 && amount <= account.bond_lamports,
+            ErrorCode::InvalidBondAmount
+        );
+        require!(
+            account.open_challenges == 0
+                && clock.unix_timestamp
+                    > account.last_attested_at + CHALLENGE_WINDOW_SECS,
+            ErrorCode::BondLocked
+        );
+
+        move_lamports(
+            &ctx.accounts.attester_vault.to_account_info(),
+            &ctx.accounts.attester.to_account_info(),
+            amount,
+        )?;
+        account.bond_lamports -= amount;
+
+        emit!(AttesterBondChanged {
+            attester: account.attester,
+            bond_lamports: account.bond_lamports,
+        });
+
+        msg!(
+            "Attester bond withdrawn: {} (bond={})",
+            account.attester,
+            account.bond_lamports
+        );
+        Ok(())
+    }
+
+    /// Post counter-evidence against an attestation within the challenge
+    /// window. The challenger escrows `CHALLENGE_BOND` lamports in the
+    /// challenge account until the platform authority resolves it.
+    pub fn challenge_attestation(
+        ctx: Context<ChallengeAttestation>,
+        evidence_hash: [u8; 32],
+    ) -> Result<()> {
+        let attestation = &ctx.accounts.attestation;
+        let clock = Clock::get()?;
+        require!(
+            clock.unix_timestamp
+                <= attestation.timestamp + CHALLENGE_WINDOW_SECS,
+            ErrorCode::ChallengeWindowClosed
+        );
+
+        invoke(
+            &system_instruction::transfer(
+                ctx.accounts.challenger.key,
+                &ctx.accounts.challenge.key(),
+                CHALLENGE_BOND,
+            ),
+            &[
+                ctx.accounts.challenger.to_account_info(),
+                ctx.accounts.challenge.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        let challenge = &mut ctx.accounts.challenge;
+        challenge.attestation = attestation.key();
+        challenge.challenger = ctx.accounts.challenger.key();
+        challenge.evidence_hash = evidence_hash;
+        challenge.created_at = clock.unix_timestamp;
+        challenge.bump = ctx.bumps.challenge;
+
+        ctx.accounts.attester_account.open_challenges += 1;
+
+        emit!(AttestationChallenged {
+            attestation: attestation.key(),
+            challenger: challenge.challenger,
+            evidence_hash,
+        });
+
+        msg!("Attestation challenged: {}", attestation.key());
+        Ok(())
+    }
+
+    /// Settle a challenge. If `upheld`, `SLASH_BPS` of the attester's bond is
+    /// split between the challenger and the treasury and the attestation's
+    /// `score_delta` is reversed on the agent. Otherwise the challenger's
+    /// escrow goes to the attester. The challenge account is closed either
+    /// way. A dispute the agent opened after the challenge must be resolved
+    /// first, or it would be left pointing at a rejected attestation.
+    pub fn resolve_challenge(
+        ctx: Context<ResolveChallenge>,
+        upheld: bool,
+    ) -> Result<()> {
+        let attestation = &mut ctx.accounts.attestation;
+        let attester_account = &mut ctx.accounts.attester_account;
+        let challenge = ctx.accounts.challenge.to_account_info();
+        require!(
+            attestation.status != AttestationStatus::Disputed,
+            ErrorCode::DisputeStillOpen
+        );
+
+        attester_account.open_challenges -= 1;
+
+        let mut slashed = 0;
+        if upheld {
+            slashed = (attester_account.bond_lamports as u128)
+                .checked_mul(SLASH_BPS as u128)
+                .unwrap()
+                .checked_div(10_000)
+                .unwrap() as u64;
+            let challenger_share = slashed / 2;
+            let treasury_share = slashed - challenger_share;
+
+            let vault = ctx.accounts.attester_vault.to_account_info();
+            move_lamports(
+                &vault,
+                &ctx.accounts.challenger.to_account_info(),
+                challenger_share,
+            )?;
+            move_lamports(
+                &vault,
+                &ctx.accounts.treasury.to_account_info(),
+                treasury_share,
+            )?;
+            attester_account.bond_lamports -= slashed;
+
+            if attestation.status == AttestationStatus::Finalized {
+                reverse_outcome(
+                    &mut ctx.accounts.agent,
+                    domain_reputation_for(
+                        &ctx.accounts.memory_log,
+                        ctx.accounts.domain_reputation.as_deref_mut(),
+                    )?,
+                    attestation.success,
+                    attestation.applied_delta,
+                    &ctx.accounts.platform_config,
+                    Clock::get()?.unix_timestamp,
+                )?;
+                record_checkpoint(
+                    &ctx.accounts.agent,
+                    &ctx.accounts.platform_config,
+                    ctx.accounts.reputation_history.as_deref_mut(),
+                )?;
+                ctx.accounts.agent.total_attestations =
+                    ctx.accounts.agent.total_attestations.saturating_sub(1);
+            }
+            attestation.status = AttestationStatus::Rejected;
+        } else {
+            move_lamports(
+                &challenge,
+                &ctx.accounts.attester_wallet.to_account_info(),
+                CHALLENGE_BOND,
+            )?;
+        }
+
+        emit!(ChallengeResolved {
+            attestation: attestation.key(),
+            challenger: ctx.accounts.challenger.key(),
+            upheld,
+            slashed_lamports: slashed,
+            new_reputation: ctx.accounts.agent.reputation,
+        });
+
+        msg!(
+            "Challenge resolved: {} (upheld={}, slashed={})",
+            attestation.key(),
+            upheld,
+            slashed
+        );
+        Ok(())
+    }
+
+    pub fn register_module(
+        ctx: Context<RegisterModule>,
+        module_id: String,
+        price_lamports: u64,
+        royalty_bps: u16,
+        ipfs_hash: String,
+    ) -> Result<()> {
+        require!(module_id.len() <= 64, ErrorCode::ModuleIdTooLong);
+        require!(ipfs_hash.len() <= 128, ErrorCode::IpfsHashTooLong);
+        require!(royalty_bps <= 10000, ErrorCode::InvalidRoyalty);
+        require!(price_lamports >= 1_000_000, ErrorCode::PriceTooLow);
+
+        let module = &mut ctx.accounts.module_metadata;
+        module.module_id = module_id.clone();
+        module.creator = ctx.accounts.creator.key();
+        module.price_lamports = price_lamports;
+        module.royalty_bps = royalty_bps;
+        module.total_sales = 0;
+        module.total_revenue = 0;
+        module.ipfs_hash = ipfs_hash.clone();
+        module.is_active = true;
+        module.bump = ctx.bumps.module_metadata;
+
+        emit!(ModuleRegistered {
+            module: module.key(),
+            module_id,
+            creator: module.creator,
+            price_lamports,
+            ipfs_hash,
+        });
+
+        msg!(
+            "Module registered: {} by {}",
+            module.module_id,
+            module.creator
+        );
+        Ok(())
+    }
+
+    pub fn purchase_module(
+        ctx: Context<PurchaseModule>,
+        referrer: Option<Pubkey>,
+    ) -> Result<()> {
+        let module = &mut ctx.accounts.module_metadata;
+        let config = &ctx.accounts.platform_config;
+        let clock = Clock::get()?;
+
+        require!(module.is_active, ErrorCode::ModuleNotActive);
+        authorize_delegated_signer(
+            &ctx.accounts.agent,
+            &ctx.accounts.buyer.key(),
+            ctx.accounts.delegate.as_mut(),
+            DelegatedAction::PurchaseModule,
+            clock.unix_timestamp,
+        )?;
+
+        let price = module.price_lamports;
+
+        let platform_fee = (price as u128)
+            .checked_mul(config.platform_fee_bps as u128)
+            .unwrap()
+            .checked_div(10_000)
+            .unwrap() as u64;
+
+        let referral_fee = if referrer.is_some() {
+            (price as u128)
+                .checked_mul(config.referral_fee_bps as u128)
+                .unwrap()
+                .checked_div(10_000)
+                .unwrap() as u64
+        } else {
+            0
+        };
+
+        let creator_royalty = price
+            .checked_sub(platform_fee)
+            .unwrap()
+            .checked_sub(referral_fee)
+            .unwrap();
+
+        // Agents registered under an organisation may spend its shared
+        // budget instead of the buyer's wallet, up to the per-member cap.
+        let payer = match ctx.accounts.organization.as_mut() {
+            Some(organization) => {
+                let info = organization.to_account_info();
+                require!(
+                    organization_budget(&info)? >= price,
+                    ErrorCode::InvalidBudgetAmount
+                );
+                let agent = &mut ctx.accounts.agent;
+                let org_spent = agent
+                    .org_spent
+                    .checked_add(price)
+                    .ok_or(ErrorCode::MemberSpendLimitExceeded)?;
+                require!(
+                    organization.member_spend_limit == 0
+                        || org_spent <= organization.member_spend_limit,
+                    ErrorCode::MemberSpendLimitExceeded
+                );
+                agent.org_spent = org_spent;
+                organization.total_spent += price;
+                PurchasePayer::Organization(info)
+            }
+            None => PurchasePayer::Buyer {
+                buyer: ctx.accounts.buyer.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+        };
+
+        payer.pay(&ctx.accounts.treasury, &config.treasury, platform_fee)?;
+        payer.pay(
+            &ctx.accounts.creator_wallet,
+            &module.creator,
+            creator_royalty,
+        )?;
+        if let Some(referrer_key) = referrer {
+            payer.pay(
+                ctx.accounts.referrer_wallet.as_ref().unwrap(),
+                &referrer_key,
+                referral_fee,
+            )?;
+        }
+
+        let purchase = &mut ctx.accounts.module_purchase;
+        purchase.agent = ctx.accounts.agent.key();
+        purchase.module = module.key();
+        purchase.purchased_at = clock.unix_timestamp;
+        purchase.price_paid = price;
+        purchase.bump = ctx.bumps.module_purchase;
+
+        module.total_sales += 1;
+        module.total_revenue += price;
+
+        emit!(ModulePurchased {
+            module: module.key(),
+            agent: ctx.accounts.agent.key(),
+            price_paid: price,
+            platform_fee,
+            creator_royalty,
+            referral_fee,
+        });
+
+        msg!(
+            "Module purchased: {} by agent {}",
+            module.module_id,
+            ctx.accounts.agent.key()
+        );
+        Ok(())
+    }
+
+    pub fn update_module_pricing(
+        ctx: Context<UpdateModulePricing>,
+        new_price: u64,
+        new_royalty_bps: u16,
+    ) -> Result<()> {
+        require!(new_price >= 1_000_000, ErrorCode::PriceTooLow);
+        require!(new_royalty_bps <= 10000, ErrorCode::InvalidRoyalty);
+
+        let module = &mut ctx.accounts.module_metadata;
+        module.price_lamports = new_price;
+        module.royalty_bps = new_royalty_bps;
+
+        msg!(
+            "Module pricing updated: {} (price={}, royalty={}bps)",
+            module.module_id,
+            new_price,
+            new_royalty_bps
+        );
+        Ok(())
+    }
+
+    pub fn deactivate_module(ctx: Context<DeactivateModule>) -> Result<()> {
+        let module = &mut ctx.accounts.module_metadata;
+        module.is_active = false;
+        msg!("Module deactivated: {}", module.module_id);
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Account Structures
+// ============================================================================
+
+#[account]
+pub struct AgentAccount {
+    pub agent_id: String,
+    pub authority: Pubkey,
+    pub created_at: i64,
+    pub reputation: i64,
+    pub total_positive: u64,
+    pub total_negative: u64,
+    pub success_count: u64,
+    pub failure_count: u64,
+    /// Consecutive successes (positive) or failures (negative).
+    pub streak: i64,
+    pub total_logs: u64,
+    /// Attestations that finalised and still stand; pending, rejected and
+    /// overturned ones are not counted.
+    pub total_attestations: u64,
+    pub chain_head: [u8; 32],
+    pub last_log: Pubkey,
+    pub last_reputation_update: i64,
+    /// Set once a `ReputationHistory` exists; every later reputation change
+    /// must then be checkpointed into it.
+    pub has_reputation_history: bool,
+    /// Sum of the weights of live endorsements from other agents.
+    pub endorsement_score: u64,
+    pub endorsement_count: u32,
+    /// Proposed new authority awaiting `accept_authority_transfer`.
+    pub pending_authority: Option<Pubkey>,
+    /// Key allowed to replace the authority outright if it is lost.
+    pub recovery_authority: Option<Pubkey>,
+    pub profile: AgentProfile,
+    /// Set by `close_agent`; a retired agent can no longer log, be
+    /// attested or buy modules.
+    pub is_retired: bool,
+    pub retired_at: i64,
+    /// `Organization` this agent was registered under, if any.
+    pub organization: Option<Pubkey>,
+    /// Reputation and log count last folded into the organisation totals
+    /// by `sync_organization_stats`.
+    pub org_reported_reputation: i64,
+    pub org_reported_logs: u64,
+    /// Set by `freeze_agent`; a frozen agent can no longer log, be attested
+    /// or buy modules until `unfreeze_agent`.
+    pub is_frozen: bool,
+    /// Reason code and evidence hash of the latest moderation action.
+    pub moderation_reason: u16,
+    pub moderation_evidence_hash: [u8; 32],
+    pub moderated_at: i64,
+    /// Statement hash of the authority's pending appeal against a freeze.
+    pub appeal_hash: Option<[u8; 32]>,
+    pub appealed_at: i64,
+    /// Number of the active `AgentVersion`; 0 before the first activation.
+    pub active_version: u32,
+    /// Bumped whenever the authority changes; `Delegate` grants from an
+    /// earlier epoch stop working.
+    pub authority_epoch: u32,
+    /// Logs written under legacy timestamp addressing, recorded by
+    /// `migrate_agent`. They take sequences `0..legacy_logs`.
+    pub legacy_logs: u64,
+    /// Migration cursor: the sequence the next `migrate_memory_log` assigns.
+    pub migrated_logs: u64,
+    /// Timestamp of the last migrated legacy log; logs migrate oldest first.
+    pub last_migrated_timestamp: i64,
+    /// Lamports spent from the organisation budget since joining, checked
+    /// against `Organization::member_spend_limit`.
+    pub org_spent: u64,
+    pub bump: u8,
+}
+
+impl AgentAccount {
+    pub const LEN: usize = 8 + // discriminator
+        68 + // agent_id (4 + 64)
+        32 + // authority
+        8 +  // created_at
+        8 +  // reputation
+        8 +  // total_positive
+        8 +  // total_negative
+        8 +  // success_count
+        8 +  // failure_count
+        8 +  // streak
+        8 +  // total_logs
+        8 +  // total_attestations
+        32 + // chain_head
+        32 + // last_log
+        8 +  // last_reputation_update
+        1 +  // has_reputation_history
+        8 +  // endorsement_score
+        4 +  // endorsement_count
+        33 + // pending_authority
+        33 + // recovery_authority
+        AgentProfile::EMPTY_LEN + // profile
+        1 +  // is_retired
+        8 +  // retired_at
+        33 + // organization
+        8 +  // org_reported_reputation
+        8 +  // org_reported_logs
+        1 +  // is_frozen
+        2 +  // moderation_reason
+        32 + // moderation_evidence_hash
+        8 +  // moderated_at
+        33 + // appeal_hash
+        8 +  // appealed_at
+        4 +  // active_version
+        4 +  // authority_epoch
+        8 +  // legacy_logs
+        8 +  // migrated_logs
+        8 +  // last_migrated_timestamp
+        8 +  // org_spent
+        1; // bump
+
+    /// Account size needed to hold `profile`.
+    pub fn space_for(profile: &AgentProfile) -> usize {
+        Self::LEN + profile.text_len()
+    }
+
+    /// Reputation at `now` after decaying the stored value by the platform
+    /// half-life since it was last updated.
+    pub fn current_reputation(&self, half_life_secs: i64, now: i64) -> i64 {
+        reputation::decay_signed(
+            self.reputation,
+            now - self.last_reputation_update,
+            half_life_secs,
+        )
+    }
+
+    /// Share of finalised outcomes that were successes, in basis points.
+    pub fn success_rate_bps(&self) -> u16 {
+        let outcomes = self.success_count + self.failure_count;
+        if outcomes == 0 {
+            return 0;
+        }
+        (self.success_count as u128 * 10_000 / outcomes as u128) as u16
+    }
+
+    /// Write the decayed reputation back and restart the decay clock.
+    /// Under `BetaBernoulli` the score is recomputed from the counts instead.
+    /// Returns whether that rescore moved the score off its decay curve,
+    /// which callers must checkpoint.
+    pub fn refresh_reputation(
+        &mut self,
+        config: &PlatformConfig,
+        now: i64,
+    ) -> bool {
+        self.reputation =
+            self.current_reputation(config.decay_half_life(), now);
+        self.last_reputation_update = now;
+        self.rescore(&config.scoring_model)
+    }
+
+    /// Under `BetaBernoulli`, reset reputation to the posterior mean of the
+    /// recorded outcome counts; other models keep the running score. This is
+    /// where an agent switched into `BetaBernoulli` jumps to its estimate.
+    /// Returns whether the score changed.
+    pub fn rescore(&mut self, scoring_model: &ScoringModel) -> bool {
+        let before = self.reputation;
+        if let ScoringModel::BetaBernoulli {
+            prior_alpha,
+            prior_beta,
+        } = *scoring_model
+        {
+            self.reputation = reputation::beta_estimate(
+                self.success_count,
+                self.failure_count,
+                prior_alpha,
+                prior_beta,
+            );
+        }
+        self.reputation != before
+    }
+
+    /// Decay reputation up to `now`, then record an outcome: score it with
+    /// the platform's model, add the result to the signed score and its
+    /// positive or negative total, and extend the success or failure streak.
+    /// Returns the delta actually applied.
+    pub fn record_outcome(
+        &mut self,
+        success: bool,
+        score_delta: i64,
+        attester_reputation: Option<i64>,
+        config: &PlatformConfig,
+        now: i64,
+    ) -> Result<i64> {
+        self.refresh_reputation(config, now);
+        let delta = config.scoring_model.effective_delta(
+            self,
+            success,
+            score_delta,
+            attester_reputation,
+        );
+        self.reputation = self
+            .reputation
+            .checked_add(delta)
+            .ok_or(ErrorCode::ReputationOverflow)?;
+        if delta >= 0 {
+            self.total_positive += delta as u64;
+        } else {
+            self.total_negative += delta.unsigned_abs();
+        }
+        if success {
+            self.success_count += 1;
+            self.streak = self.streak.max(0) + 1;
+        } else {
+            self.failure_count += 1;
+            self.streak = self.streak.min(0) - 1;
+        }
+        Ok(delta)
+    }
+
+    /// Undo an outcome previously passed to `record_outcome`. The streak
+    /// can't be rebuilt after the fact, so it restarts from zero.
+    pub fn reverse_outcome(
+        &mut self,
+        success: bool,
+        delta: i64,
+        config: &PlatformConfig,
+        now: i64,
+    ) -> Result<()> {
+        self.refresh_reputation(config, now);
+        self.reputation = self
+            .reputation
+            .checked_sub(delta)
+            .ok_or(ErrorCode::ReputationOverflow)?;
+        if delta >= 0 {
+            self.total_positive =
+                self.total_positive.saturating_sub(delta as u64);
+        } else {
+            self.total_negative =
+                self.total_negative.saturating_sub(delta.unsigned_abs());
+        }
+        if success {
+            self.success_count = self.success_count.saturating_sub(1);
+        } else {
+            self.failure_count = self.failure_count.saturating_sub(1);
+        }
+        self.streak = 0;
+        self.rescore(&config.scoring_model);
+        Ok(())
+    }
+}
+
+/// How long a retired agent's logs and attestations must be kept before
+/// anyone may close them and return their rent to the agent authority.
+pub const RETENTION_PERIOD_SECS: i64 = 90 * 24 * 60 * 60;
+
+pub const MAX_DISPLAY_NAME_LEN: usize = 64;
+pub const MAX_DESCRIPTION_LEN: usize = 256;
+pub const MAX_METADATA_URI_LEN: usize = 200;
+pub const MAX_MODEL_LEN: usize = 64;
+
+/// Self-reported description of an agent. Text fields are sized to their
+/// contents, so the agent account is reallocated whenever they change.
+#[derive(
+    AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq,
+)]
+pub struct AgentProfile {
+    pub display_name: String,
+    pub description: String,
+    /// Off-chain metadata document and the hash of its contents.
+    pub metadata_uri: String,
+    pub metadata_hash: [u8; 32],
+    /// Bitmap of capabilities; bit meanings are defined off-chain.
+    pub capabilities: u64,
+    /// Hash of the operator's contact details.
+    pub operator_contact_hash: [u8; 32],
+    /// Model or agent framework identifier, e.g. "llama-3/eliza".
+    pub model: String,
+}
+
+impl AgentProfile {
+    /// Serialized size with every text field empty.
+    pub const EMPTY_LEN: usize = 4 + // display_name
+        4 +  // description
+        4 +  // metadata_uri
+        32 + // metadata_hash
+        8 +  // capabilities
+        32 + // operator_contact_hash
+        4; // model
+
+    /// Bytes taken by the text fields beyond `EMPTY_LEN`.
+    pub fn text_len(&self) -> usize {
+        self.display_name.len()
+            + self.description.len()
+            + self.metadata_uri.len()
+            + self.model.len()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.display_name.len() <= MAX_DISPLAY_NAME_LEN
+                && self.description.len() <= MAX_DESCRIPTION_LEN
+                && self.metadata_uri.len() <= MAX_METADATA_URI_LEN
+                && self.model.len() <= MAX_MODEL_LEN,
+            ErrorCode::ProfileFieldTooLong
+        );
+        Ok(())
+    }
+}
+
+/// `AgentAccount` layout used before decision chaining. Only read by
+/// `migrate_agent`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyAgentAccount {
+    pub agent_id: String,
+    pub authority: Pubkey,
+    pub reputation: u64,
+    pub total_logs: u64,
+    pub total_attestations: u64,
+    pub bump: u8,
+}
+
+impl LegacyAgentAccount {
+    pub const LEN: usize = 8 + 68 + 32 + 8 + 8 + 8 + 1;
+}
+
+#[account]
+pub struct MemoryLog {
+    pub agent: Pubkey,
+    pub sequence: u64,
+    pub input_hash: [u8; 32],
+    pub logic_hash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub timestamp: i64,
+    pub is_attested: bool,
+    pub is_commitment: bool,
+    pub is_revealed: bool,
+    pub reveal_after: i64,
+    pub reveal_deadline: i64,
+    pub reveal_missed: bool,
+    pub domain: String,
+    pub prev_log: Pubkey,
+    pub prev_root: [u8; 32],
+    /// `AgentVersion` active when the decision was logged (0 for none).
+    pub version: u32,
+    /// Set once the attested outcome is counted by `tally_version_outcome`.
+    pub version_tallied: bool,
+    pub bump: u8,
+}
+
+impl MemoryLog {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // agent
+        8 +  // sequence
+        32 + // input_hash
+        32 + // logic_hash
+        32 + // merkle_root
+        8 +  // timestamp
+        1 +  // is_attested
+        1 +  // is_commitment
+        1 +  // is_revealed
+        8 +  // reveal_after
+        8 +  // reveal_deadline
+        1 +  // reveal_missed
+        4 + MAX_DOMAIN_LEN + // domain
+        32 + // prev_log
+        32 + // prev_root
+        4 +  // version
+        1 +  // version_tallied
+        1; // bump
+
+    /// True once a committed decision is past its reveal deadline unopened.
+    pub fn is_reveal_overdue(&self, now: i64) -> bool {
+        self.is_commitment
+            && !self.is_revealed
+            && self.reveal_deadline != 0
+            && now > self.reveal_deadline
+    }
+}
+
+impl MemoryLog {
+    /// A sealed decision that was never opened counts as a failure: flag it
+    /// and force the attested outcome to at least `MISSED_REVEAL_PENALTY`.
+    pub fn penalise_missed_reveal(
+        &mut self,
+        now: i64,
+        success: bool,
+        score_delta: i64,
+    ) -> (bool, i64) {
+        if self.reveal_missed || self.is_reveal_overdue(now) {
+            self.reveal_missed = true;
+            (false, score_delta.min(-MISSED_REVEAL_PENALTY))
+        } else {
+            (success, score_delta)
+        }
+    }
+}
+
+/// Maximum length of a reputation domain tag.
+pub const MAX_DOMAIN_LEN: usize = 32;
+
+/// An agent's reputation within one domain, alongside the global score on
+/// `AgentAccount`.
+#[account]
+pub struct DomainReputation {
+    pub agent: Pubkey,
+    pub domain: String,
+    pub reputation: i64,
+    pub total_attestations: u64,
+    /// Finalised outcomes in this domain; `BetaBernoulli` scores from these.
+    pub success_count: u64,
+    pub failure_count: u64,
+    pub last_reputation_update: i64,
+    pub bump: u8,
+}
+
+impl DomainReputation {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // agent
+        4 + MAX_DOMAIN_LEN + // domain
+        8 +  // reputation
+        8 +  // total_attestations
+        8 +  // success_count
+        8 +  // failure_count
+        8 +  // last_reputation_update
+        1; // bump
+
+    /// Record an outcome the global score changed by `delta`: decay up to
+    /// `now` and add the same delta. Under `BetaBernoulli` the domain holds
+    /// its own posterior mean over its own counts instead, since the global
+    /// delta is a change in the global estimate.
+    pub fn record_outcome(
+        &mut self,
+        success: bool,
+        delta: i64,
+        config: &PlatformConfig,
+        now: i64,
+    ) -> Result<()> {
+        self.add_delta(delta, config, now)?;
+        if success {
+            self.success_count += 1;
+        } else {
+            self.failure_count += 1;
+        }
+        self.total_attestations += 1;
+        self.rescore(&config.scoring_model);
+        Ok(())
+    }
+
+    /// Undo an outcome previously passed to `record_outcome`.
+    pub fn reverse_outcome(
+        &mut self,
+        success: bool,
+        delta: i64,
+        config: &PlatformConfig,
+        now: i64,
+    ) -> Result<()> {
+        self.add_delta(-delta, config, now)?;
+        if success {
+            self.success_count = self.success_count.saturating_sub(1);
+        } else {
+            self.failure_count = self.failure_count.saturating_sub(1);
+        }
+        self.total_attestations = self.total_attestations.saturating_sub(1);
+        self.rescore(&config.scoring_model);
+        Ok(())
+    }
+
+    /// Decay up to `now`, then add `delta`.
+    fn add_delta(
+        &mut self,
+        delta: i64,
+        config: &PlatformConfig,
+        now: i64,
+    ) -> Result<()> {
+        let decayed = reputation::decay_signed(
+            self.reputation,
+            now - self.last_reputation_update,
+            config.decay_half_life(),
+        );
+        self.reputation = decayed
+            .checked_add(delta)
+            .ok_or(ErrorCode::ReputationOverflow)?;
+        self.last_reputation_update = now;
+        Ok(())
+    }
+
+    /// Under `BetaBernoulli`, reset the score to the domain's posterior mean.
+    fn rescore(&mut self, scoring_model: &ScoringModel) {
+        if let ScoringModel::BetaBernoulli {
+            prior_alpha,
+            prior_beta,
+        } = *scoring_model
+        {
+            self.reputation = reputation::beta_estimate(
+                self.success_count,
+                self.failure_count,
+                prior_alpha,
+                prior_beta,
+            );
+        }
+    }
+}
+
+/// A session key allowed to log decisions for an agent (and, with
+/// `can_purchase`, to buy modules for it) until `expires_at`, for at most
+/// `max_logs` logs.
+#[account]
+pub struct Delegate {
+    pub agent: Pubkey,
+    pub delegate: Pubkey,
+    pub can_purchase: bool,
+    pub expires_at: i64,
+    pub max_logs: u64,
+    pub logs_used: u64,
+    /// `AgentAccount::authority_epoch` when granted.
+    pub authority_epoch: u32,
+    pub bump: u8,
+}
+
+impl Delegate {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // agent
+        32 + // delegate
+        1 +  // can_purchase
+        8 +  // expires_at
+        8 +  // max_logs
+        8 +  // logs_used
+        4 +  // authority_epoch
+        1; // bump
+}
+
+/// One agent vouching for another. The weight is the endorser's reputation
+/// when it endorsed or when `refresh_endorsement` last re-weighed it.
+#[account]
+pub struct Endorsement {
+    pub endorser: Pubkey,
+    pub endorsee: Pubkey,
+    pub weight: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Endorsement {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // endorser
+        32 + // endorsee
+        8 +  // weight
+        8 +  // created_at
+        1; // bump
+}
+
+/// One model/prompt configuration of an agent. Logs record the version
+/// active when they were written, and `tally_version_outcome` credits each
+/// attested outcome to that version rather than to whichever runs now.
+#[account]
+pub struct AgentVersion {
+    pub agent: Pubkey,
+    pub version: u32,
+    pub model_hash: [u8; 32],
+    pub config_hash: [u8; 32],
+    pub activated_at: i64,
+    pub success_count: u64,
+    pub failure_count: u64,
+    /// Sum of the reputation deltas applied to this version's outcomes.
+    pub reputation: i64,
+    pub bump: u8,
+}
+
+impl AgentVersion {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // agent
+        4 +  // version
+        32 + // model_hash
+        32 + // config_hash
+        8 +  // activated_at
+        8 +  // success_count
+        8 +  // failure_count
+        8 +  // reputation
+        1; // bump
+
+    /// Share of tallied outcomes that succeeded, in basis points.
+    pub fn success_rate_bps(&self) -> u16 {
+        let total = self.success_count + self.failure_count;
+        if total == 0 {
+            return 0;
+        }
+        (self.success_count as u128 * 10_000 / total as u128) as u16
+    }
+}
+
+pub const MAX_ORGANIZATION_ADMINS: usize = 8;
+
+/// A team-owned group of agents. Lamports held above rent are a shared
+/// budget member agents can spend on modules; the totals are refreshed per
+/// member by `sync_organization_stats`.
+#[account]
+pub struct Organization {
+    pub org_id: String,
+    pub admins: Vec<Pubkey>,
+    pub agent_count: u32,
+    pub total_reputation: i64,
+    pub total_logs: u64,
+    /// Lamports spent from the budget on module purchases.
+    pub total_spent: u64,
+    /// Most a single member may spend from the budget; 0 means no cap.
+    pub member_spend_limit: u64,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Organization {
+    pub const LEN: usize = 8 + // discriminator
+        4 + MAX_AGENT_ID_LEN + // org_id
+        4 + MAX_ORGANIZATION_ADMINS * 32 + // admins
+        4 +  // agent_count
+        8 +  // total_reputation
+        8 +  // total_logs
+        8 +  // total_spent
+        8 +  // member_spend_limit
+        8 +  // created_at
+        1; // bump
+}
+
+/// Minimum standing checked by `require_reputation`. Zero (or `i64::MIN`
+/// for reputation) disables a threshold.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ReputationRequirements {
+    pub min_reputation: i64,
+    /// Finalised outcomes, successful or not.
+    pub min_outcomes: u64,
+    pub min_success_rate_bps: u16,
+    pub min_account_age_secs: i64,
+}
+
+/// An agent's standing as returned by `require_reputation`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ReputationStanding {
+    /// Reputation decayed to the current time.
+    pub reputation: i64,
+    pub outcomes: u64,
+    pub success_rate_bps: u16,
+    pub account_age_secs: i64,
+}
+
+/// Number of checkpoints a `ReputationHistory` retains.
+pub const MAX_REPUTATION_CHECKPOINTS: usize = 32;
+
+/// An agent's stored reputation right after it changed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct ReputationCheckpoint {
+    pub slot: u64,
+    pub timestamp: i64,
+    pub reputation: i64,
+    /// Decay half-life in effect when the checkpoint was taken.
+    pub half_life_secs: i64,
+}
+
+impl ReputationCheckpoint {
+    pub const LEN: usize = 8 + 8 + 8 + 8;
+}
+
+/// Ring buffer of an agent's most recent reputation checkpoints, for
+/// answering what its reputation was at a past time or slot.
+#[account]
+pub struct ReputationHistory {
+    pub agent: Pubkey,
+    /// Slot the next checkpoint overwrites once the buffer is full.
+    pub next: u16,
+    pub checkpoints: Vec<ReputationCheckpoint>,
+    pub bump: u8,
+}
+
+impl ReputationHistory {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // agent
+        2 +  // next
+        4 + MAX_REPUTATION_CHECKPOINTS * ReputationCheckpoint::LEN + // checkpoints
+        1; // bump
+
+    pub fn push(&mut self, checkpoint: ReputationCheckpoint) {
+        if self.checkpoints.len() < MAX_REPUTATION_CHECKPOINTS {
+            self.checkpoints.push(checkpoint);
+        } else {
+            self.checkpoints[self.next as usize] = checkpoint;
+        }
+        self.next =
+            ((self.next as usize + 1) % MAX_REPUTATION_CHECKPOINTS) as u16;
+    }
+
+    /// The latest retained checkpoint taken at or before `timestamp`.
+    pub fn checkpoint_at(
+        &self,
+        timestamp: i64,
+    ) -> Option<&ReputationCheckpoint> {
+        self.checkpoints
+            .iter()
+            .filter(|checkpoint| checkpoint.timestamp <= timestamp)
+            .max_by_key(|checkpoint| (checkpoint.timestamp, checkpoint.slot))
+    }
+
+    /// The latest retained checkpoint taken at or before `slot`.
+    pub fn checkpoint_at_slot(
+        &self,
+        slot: u64,
+    ) -> Option<&ReputationCheckpoint> {
+        self.checkpoints
+            .iter()
+            .filter(|checkpoint| checkpoint.slot <= slot)
+            .max_by_key(|checkpoint| checkpoint.slot)
+    }
+
+    /// Reputation valid at `timestamp`: the checkpoint in force then,
+    /// decayed by its own half-life up to `timestamp`. `None` if the time
+    /// predates every retained checkpoint.
+    pub fn reputation_at(&self, timestamp: i64) -> Option<i64> {
+        self.checkpoint_at(timestamp).map(|checkpoint| {
+            reputation::decay_signed(
+                checkpoint.reputation,
+                timestamp - checkpoint.timestamp,
+                checkpoint.half_life_secs,
+            )
+        })
+    }
+}
+
+/// Minimum reputation penalty applied to an attested committed decision
+/// that missed its reveal deadline.
+pub const MISSED_REVEAL_PENALTY: i64 = 10;
+
+/// `MemoryLog` layout used before counter-based addressing, when logs were
+/// seeded with `[b"memory", agent, timestamp]`. Only read by `migrate_memory_log`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyMemoryLog {
+    pub agent: Pubkey,
+    pub input_hash: [u8; 32],
+    pub logic_hash: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub timestamp: i64,
+    pub is_attested: bool,
+    pub bump: u8,
+}
+
+impl LegacyMemoryLog {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 32 + 8 + 1 + 1;
+}
+
+/// Maximum number of decisions accepted by a single `log_decision_batch`.
+pub const MAX_BATCH_LEAVES: usize = 16;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DecisionLeaf {
+    pub input_hash: [u8; 32],
+    pub logic_hash: [u8; 32],
+}
+
+#[account]
+pub struct DecisionBatch {
+    pub agent: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub leaf_count: u32,
+    pub first_sequence: u64,
+    pub last_sequence: u64,
+    pub timestamp: i64,
+    pub prev_log: Pubkey,
+    pub prev_root: [u8; 32],
+    /// `AgentVersion` active when the batch was logged (0 for none).
+    pub version: u32,
+    pub bump: u8,
+}
+
+impl DecisionBatch {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // agent
+        32 + // merkle_root
+        4 +  // leaf_count
+        8 +  // first_sequence
+        8 +  // last_sequence
+        8 +  // timestamp
+        32 + // prev_log
+        32 + // prev_root
+        4 +  // version
+        1; // bump
+}
+
+/// A `MemoryLog` or `DecisionBatch` viewed as a link in the decision chain.
+pub struct ChainEntry {
+    pub key: Pubkey,
+    pub agent: Pubkey,
+    pub prev_log: Pubkey,
+    pub prev_root: [u8; 32],
+    pub merkle_root: [u8; 32],
+    pub first_sequence: u64,
+    pub last_sequence: u64,
+}
+
+impl ChainEntry {
+    pub fn load<'info>(info: &'info AccountInfo<'info>) -> Result<Self> {
+        let is_batch = info.try_borrow_data()?.get(..8)
+            == Some(&DecisionBatch::DISCRIMINATOR);
+        if is_batch {
+            let batch = Account::<DecisionBatch>::try_from(info)?;
+            Ok(Self {
+                key: batch.key(),
+                agent: batch.agent,
+                prev_log: batch.prev_log,
+                prev_root: batch.prev_root,
+                merkle_root: batch.merkle_root,
+                first_sequence: batch.first_sequence,
+                last_sequence: batch.last_sequence,
+            })
+        } else {
+            let log = Account::<MemoryLog>::try_from(info)?;
+            Ok(Self {
+                key: log.key(),
+                agent: log.agent,
+                prev_log: log.prev_log,
+                prev_root: log.prev_root,
+                merkle_root: log.merkle_root,
+                first_sequence: log.sequence,
+                last_sequence: log.sequence,
+            })
+        }
+    }
+
+    pub fn link(&self) -> [u8; 32] {
+        chain::link(
+            &self.prev_root,
+            &self.merkle_root,
+            self.first_sequence,
+            self.last_sequence,
+        )
+    }
+}
+
+#[account]
+pub struct Attestation {
+    pub memory_log: Pubkey,
+    pub attester: Pubkey,
+    pub outcome_hash: [u8; 32],
+    pub success: bool,
+    pub score_delta: i64,
+    /// The attester's own agent reputation when attesting, used by
+    /// `ScoringModel::Elo`.
+    pub attester_reputation: Option<i64>,
+    /// Reputation change actually applied on finalisation.
+    pub applied_delta: i64,
+    pub timestamp: i64,
+    pub status: AttestationStatus,
+    pub dispute_deadline: i64,
+    pub bump: u8,
+}
+
+impl Attestation {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // memory_log
+        32 + // attester
+        32 + // outcome_hash
+        1 +  // success
+        8 +  // score_delta
+        9 +  // attester_reputation
+        8 +  // applied_delta
+        8 +  // timestamp
+        1 +  // status
+        8 +  // dispute_deadline
+        1; // bump
+}
+
+/// Lifecycle of an attestation. Reputation only moves on `Finalized`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AttestationStatus {
+    /// Inside the dispute window; `score_delta` not yet applied.
+    Pending,
+    /// Contested by the agent and awaiting the arbiter.
+    Disputed,
+    /// `score_delta` has been applied to the agent.
+    Finalized,
+    /// Overturned by the arbiter or by an upheld challenge.
+    Rejected,
+}
+
+/// Maximum number of attesters in a quorum attestation round.
+pub const MAX_ROUND_VOTERS: usize = 9;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RoundVote {
+    pub attester: Pubkey,
+    pub success: bool,
+    pub score_delta: i64,
+}
+
+impl RoundVote {
+    pub const LEN: usize = 32 + 1 + 8;
+}
+
+/// Time a round has to reach quorum before it can be cancelled.
+pub const ROUND_EXPIRY_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// Votes from several attesters on one memory log, settled once `quorum`
+/// votes are in.
+#[account]
+pub struct AttestationRound {
+    pub memory_log: Pubkey,
+    pub agent: Pubkey,
+    /// Platform authority or attester that opened the round; refunded its
+    /// rent on cancellation.
+    pub opener: Pubkey,
+    pub quorum: u8,
+    pub votes: Vec<RoundVote>,
+    pub is_finalized: bool,
+    pub success: bool,
+    pub score_delta: i64,
+    pub opened_at: i64,
+    pub finalized_at: i64,
+    pub bump: u8,
+}
+
+impl AttestationRound {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // memory_log
+        32 + // agent
+        32 + // opener
+        1 +  // quorum
+        4 + RoundVote::LEN * MAX_ROUND_VOTERS + // votes
+        1 +  // is_finalized
+        1 +  // success
+        8 +  // score_delta
+        8 +  // opened_at
+        8 +  // finalized_at
+        1; // bump
+
+    /// Majority `success` (ties count as failure) and median `score_delta`
+    /// (mean of the two middle votes for an even count).
+    pub fn tally(&self) -> (bool, i64) {
+        let successes = self.votes.iter().filter(|vote| vote.success).count();
+        let success = successes * 2 > self.votes.len();
+
+        let mut deltas: Vec<i64> =
+            self.votes.iter().map(|vote| vote.score_delta).collect();
+        deltas.sort_unstable();
+        let mid = deltas.len() / 2;
+        let median = if mid * 2 == deltas.len() {
+            ((deltas[mid - 1] as i128 + deltas[mid] as i128) / 2) as i64
+        } else {
+            deltas[mid]
+        };
+        (success, median)
+    }
+}
+
+/// Default time an attestation stays disputable.
+pub const DEFAULT_DISPUTE_WINDOW_SECS: i64 = 3 * 24 * 60 * 60;
+/// Upper bound the platform authority can set for the dispute window.
+pub const MAX_DISPUTE_WINDOW_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// Agent's dispute of an attestation and the arbiter's ruling on it.
+#[account]
+pub struct Dispute {
+    pub attestation: Pubkey,
+    pub agent: Pubkey,
+    pub evidence_hash: [u8; 32],
+    pub filed_at: i64,
+    pub resolved_at: i64,
+    pub attestation_upheld: bool,
+    pub bump: u8,
+}
+
+impl Dispute {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // attestation
+        32 + // agent
+        32 + // evidence_hash
+        8 +  // filed_at
+        8 +  // resolved_at
+        1 +  // attestation_upheld
+        1; // bump
+}
+
+/// Registry entry for a key allowed to attest outcomes, managed by the
+/// platform authority.
+#[account]
+pub struct AttesterAccount {
+    pub attester: Pubkey,
+    /// The attester's own agent, linked once by `link_attester_agent`;
+    /// `ScoringModel::Elo` rates attested agents against it.
+    pub agent: Option<Pubkey>,
+    pub is_active: bool,
+    pub registered_at: i64,
+    pub total_attestations: u64,
+    pub bond_lamports: u64,
+    pub open_challenges: u32,
+    pub last_attested_at: i64,
+    pub bump: u8,
+}
+
+impl AttesterAccount {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // attester
+        33 + // agent
+        1 +  // is_active
+        8 +  // registered_at
+        8 +  // total_attestations
+        8 +  // bond_lamports
+        4 +  // open_challenges
+        8 +  // last_attested_at
+        1; // bump
+}
+
+/// Minimum bond an attester must hold to call `attest_outcome`.
+pub const MIN_ATTESTER_BOND: u64 = 100_000_000;
+/// Lamports a challenger escrows when disputing an attestation.
+pub const CHALLENGE_BOND: u64 = 10_000_000;
+/// How long after an attestation it may still be challenged.
+pub const CHALLENGE_WINDOW_SECS: i64 = 7 * 24 * 60 * 60;
+/// Share of the attester's bond slashed by an upheld challenge.
+pub const SLASH_BPS: u16 = 5000;
+
+/// Holds an attester's bonded SOL above its own rent.
+#[account]
+pub struct AttesterVault {
+    pub bump: u8,
+}
+
+impl AttesterVault {
+    pub const LEN: usize = 8 + 1;
+}
+
+/// Open dispute against an attestation, escrowing the challenger's bond.
+#[account]
+pub struct Challenge {
+    pub attestation: Pubkey,
+    pub challenger: Pubkey,
+    pub evidence_hash: [u8; 32],
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Challenge {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // attestation
+        32 + // challenger
+        32 + // evidence_hash
+        8 +  // created_at
+        1; // bump
+}
+
+/// Maximum number of attesters on a per-agent allowlist.
+pub const MAX_ALLOWED_ATTESTERS: usize = 10;
+
+/// Optional per-agent restriction on which registered attesters may attest.
+#[account]
+pub struct AttesterAllowlist {
+    pub agent: Pubkey,
+    pub attesters: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl AttesterAllowlist {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // agent
+        4 + 32 * MAX_ALLOWED_ATTESTERS + // attesters
+        1; // bump
+}
+
+#[account]
+pub struct ModuleMetadata {
+    pub module_id: String,
+    pub creator: Pubkey,
+    pub price_lamports: u64,
+    pub royalty_bps: u16,
+    pub total_sales: u64,
+    pub total_revenue: u64,
+    pub ipfs_hash: String,
+    pub is_active: bool,
+    pub bump: u8,
+}
+
+#[account]
+pub struct ModulePurchase {
+    pub agent: Pubkey,
+    pub module: Pubkey,
+    pub purchased_at: i64,
+    pub price_paid: u64,
+    pub bump: u8,
+}
+
+#[account]
+pub struct PlatformConfig {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub platform_fee_bps: u16,
+    pub referral_fee_bps: u16,
+    pub arbiter: Pubkey,
+    pub dispute_window_secs: i64,
+    pub reputation_half_life_secs: i64,
+    pub scoring_model: ScoringModel,
+    /// Agent IDs only the platform authority may register.
+    pub reserved_names: Vec<String>,
+    /// Price of a `NameReservation` for one `NAME_RESERVATION_PERIOD_SECS`.
+    pub name_reservation_fee: u64,
+    pub bump: u8,
+}
+
+impl PlatformConfig {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // authority
+        32 + // treasury
+        2 +  // platform_fee_bps
+        2 +  // referral_fee_bps
+        32 + // arbiter
+        8 +  // dispute_window_secs
+        8 +  // reputation_half_life_secs
+        ScoringModel::LEN + // scoring_model
+        4 + MAX_RESERVED_NAMES * (4 + MAX_AGENT_ID_LEN) + // reserved_names
+        8 +  // name_reservation_fee
+        1; // bump
+
+    /// Half-life applied to reputation. A `BetaBernoulli` score is a
+    /// posterior mean, not a running total, so it never decays.
+    pub fn decay_half_life(&self) -> i64 {
+        match self.scoring_model {
+            ScoringModel::BetaBernoulli { .. } => 0,
+            _ => self.reputation_half_life_secs,
+        }
+    }
+}
+
+pub const MAX_AGENT_ID_LEN: usize = 64;
+pub const MAX_RESERVED_NAMES: usize = 16;
+pub const NAME_RESERVATION_PERIOD_SECS: i64 = 365 * 24 * 60 * 60;
+pub const DEFAULT_NAME_RESERVATION_FEE: u64 = 100_000_000; // 0.1 SOL
+
+/// A paid hold on an agent ID: until `expires_at` only `owner` may
+/// register an agent under it.
+#[account]
+pub struct NameReservation {
+    pub name: String,
+    pub owner: Pubkey,
+    pub expires_at: i64,
+    pub bump: u8,
+}
+
+impl NameReservation {
+    pub const LEN: usize = 8 + // discriminator
+        4 + MAX_AGENT_ID_LEN + // name
+        32 + // owner
+        8 +  // expires_at
+        1; // bump
+}
+
+/// Upper bound on `ScoringModel::Elo::k_factor`.
+pub const MAX_ELO_K_FACTOR: u32 = 1_000;
+
+/// How an attested outcome turns into a reputation change.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ScoringModel {
+    /// Add the attested `score_delta` unchanged.
+    Raw,
+    /// Add the attested `score_delta`, clamped to `±max_delta`.
+    Capped { max_delta: i64 },
+    /// Ignore `score_delta` and apply an Elo update of up to `k_factor`,
+    /// rating the agent against the attester's own agent reputation.
+    Elo { k_factor: u32 },
+    /// Hold reputation at the posterior mean success rate, in basis points,
+    /// under a `Beta(prior_alpha, prior_beta)` prior. The estimate only moves
+    /// with evidence, so the platform half-life does not apply.
+    BetaBernoulli { prior_alpha: u32, prior_beta: u32 },
+}
+
+impl ScoringModel {
+    pub const LEN: usize = 1 + 8; // tag + largest variant
+
+    pub fn validate(&self) -> Result<()> {
+        let valid = match *self {
+            ScoringModel::Raw => true,
+            ScoringModel::Capped { max_delta } => max_delta > 0,
+            ScoringModel::Elo { k_factor } => {
+                (1..=MAX_ELO_K_FACTOR).contains(&k_factor)
+            }
+            ScoringModel::BetaBernoulli {
+                prior_alpha,
+                prior_beta,
+            } => prior_alpha > 0 && prior_beta > 0,
+        };
+        require!(valid, ErrorCode::InvalidScoringModel);
+        Ok(())
+    }
+
+    /// The reputation change for an outcome on `agent`, whose reputation
+    /// must already be decayed to now. `attester_reputation` is the rating
+    /// Elo plays the agent against; `None` rates it against itself.
+    pub fn effective_delta(
+        &self,
+        agent: &AgentAccount,
+        success: bool,
+        score_delta: i64,
+        attester_reputation: Option<i64>,
+    ) -> i64 {
+        match *self {
+            ScoringModel::Raw => score_delta,
+            ScoringModel::Capped { max_delta } => {
+                score_delta.clamp(-max_delta, max_delta)
+            }
+            ScoringModel::Elo { k_factor } => reputation::elo_delta(
+                agent.reputation,
+                attester_reputation.unwrap_or(agent.reputation),
+                k_factor,
+                success,
+            ),
+            ScoringModel::BetaBernoulli {
+                prior_alpha,
+                prior_beta,
+            } => {
+                let (successes, failures) = if success {
+                    (agent.success_count + 1, agent.failure_count)
+                } else {
+                    (agent.success_count, agent.failure_count + 1)
+                };
+                reputation::beta_estimate(
+                    successes,
+                    failures,
+                    prior_alpha,
+                    prior_beta,
+                )
+                .saturating_sub(agent.reputation)
+            }
+        }
+    }
+}
+
+/// `PlatformConfig` layout used before dispute settings. Only read by
+/// `migrate_platform_config`.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct LegacyPlatformConfig {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub platform_fee_bps: u16,
+    pub referral_fee_bps: u16,
+    pub bump: u8,
+}
+
+impl LegacyPlatformConfig {
+    pub const LEN: usize = 8 + 32 + 32 + 2 + 2 + 1;
+}
+
+// ============================================================================
+// Context Structures
+// ============================================================================
+
+#[derive(Accounts)]
+#[instruction(agent_id: String)]
+pub struct InitializeAgent<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = AgentAccount::LEN,
+        seeds = [b"agent", agent_id.as_bytes()],
+        bump
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// CHECK: Name reservation PDA; only enforced if initialized.
+    #[account(
+        seeds = [b"name_reservation", agent_id.as_bytes()],
+        bump
+    )]
+    pub name_reservation: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// Organisation to register the agent under; `authority` must be one of
+    /// its admins.
+    #[account(
+        mut,
+        seeds = [b"organization", organization.org_id.as_bytes()],
+        bump = organization.bump
+    )]
+    pub organization: Option<Account<'info, Organization>>,
+}
+
+#[derive(Accounts)]
+pub struct LogDecision<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        constraint = !agent.is_retired @ ErrorCode::AgentRetired,
+        constraint = !agent.is_frozen @ ErrorCode::AgentFrozen
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = MemoryLog::LEN,
+        seeds = [b"memory", agent.key().as_ref(), &agent.total_logs.to_le_bytes()],
+        bump
+    )]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    /// The agent authority, or a session key holding a `Delegate` grant.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [b"delegate", agent.key().as_ref(), authority.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+}
+
+#[derive(Accounts)]
+pub struct RevealDecision<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        has_one = agent
+    )]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FlagMissedReveal<'info> {
+    #[account(mut)]
+    pub memory_log: Account<'info, MemoryLog>,
+}
+
+#[derive(Accounts)]
+pub struct LogDecisionBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority,
+        constraint = !agent.is_retired @ ErrorCode::AgentRetired,
+        constraint = !agent.is_frozen @ ErrorCode::AgentFrozen
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = DecisionBatch::LEN,
+        seeds = [b"batch", agent.key().as_ref(), &agent.total_logs.to_le_bytes()],
+        bump
+    )]
+    pub batch: Account<'info, DecisionBatch>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyDecisionInclusion<'info> {
+    pub memory_log: Option<Account<'info, MemoryLog>>,
+
+    pub batch: Option<Account<'info, DecisionBatch>>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyLogChain<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AgentAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: String)]
+pub struct MigrateAgent<'info> {
+    /// CHECK: Legacy agent account; owner, discriminator, layout and
+    /// authority are verified in the handler before it is rewritten.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"agent", agent_id.as_bytes()],
+        bump
+    )]
+    pub agent: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMemoryLog<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    /// CHECK: Legacy timestamp-seeded log; owner, discriminator, layout and
+    /// address are all verified in the handler before it is rewritten.
+    #[account(mut, owner = crate::ID)]
+    pub legacy_memory_log: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AttestOutcome<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        constraint = !agent.is_retired @ ErrorCode::AgentRetired,
+        constraint = !agent.is_frozen @ ErrorCode::AgentFrozen
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        has_one = agent
+    )]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    #[account(
+        init,
+        payer = attester,
+        space = Attestation::LEN,
+        seeds = [b"attest", memory_log.key().as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        mut,
+        seeds = [b"attester", attester.key().as_ref()],
+        bump = attester_account.bump,
+        constraint = attester_account.attester != agent.authority
+            @ ErrorCode::SelfAttestation,
+        constraint = attester_account.is_active @ ErrorCode::AttesterNotActive,
+        constraint = attester_account.bond_lamports >= MIN_ATTESTER_BOND
+            @ ErrorCode::InsufficientAttesterBond
+    )]
+    pub attester_account: Account<'info, AttesterAccount>,
+
+    /// CHECK: Agent's attester allowlist PDA; only enforced if initialized.
+    #[account(
+        seeds = [b"attester_allowlist", agent.key().as_ref()],
+        bump
+    )]
+    pub attester_allowlist: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(mut)]
+    pub attester: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"domain_rep",
+            agent.key().as_ref(),
+            memory_log.domain.as_bytes()
+        ],
+        bump = domain_reputation.bump
+    )]
+    pub domain_reputation: Option<Account<'info, DomainReputation>>,
+
+    /// The agent linked to the attester; its reputation weights
+    /// `ScoringModel::Elo`, which requires it.
+    #[account(
+        constraint = attester_account.agent == Some(attester_agent.key())
+            @ ErrorCode::AttesterAgentMismatch
+    )]
+    pub attester_agent: Option<Account<'info, AgentAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation_history", agent.key().as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Option<Account<'info, ReputationHistory>>,
+}
+
+#[derive(Accounts)]
+pub struct OpenAttestationRound<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        constraint = !agent.is_retired @ ErrorCode::AgentRetired,
+        constraint = !agent.is_frozen @ ErrorCode::AgentFrozen
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(mut, has_one = agent)]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    #[account(
+        init,
+        payer = opener,
+        space = AttestationRound::LEN,
+        seeds = [b"round", memory_log.key().as_ref()],
+        bump
+    )]
+    pub round: Account<'info, AttestationRound>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// The platform authority, or a registered attester passing
+    /// `opener_attester`.
+    #[account(mut)]
+    pub opener: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"attester", opener.key().as_ref()],
+        bump = opener_attester.bump
+    )]
+    pub opener_attester: Option<Account<'info, AttesterAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitRoundVote<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        constraint = !agent.is_retired @ ErrorCode::AgentRetired,
+        constraint = !agent.is_frozen @ ErrorCode::AgentFrozen
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(has_one = agent)]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    #[account(
+        mut,
+        seeds = [b"round", memory_log.key().as_ref()],
+        bump = round.bump
+    )]
+    pub round: Account<'info, AttestationRound>,
+
+    #[account(
+        mut,
+        seeds = [b"attester", attester.key().as_ref()],
+        bump = attester_account.bump,
+        constraint = attester_account.attester != agent.authority
+            @ ErrorCode::SelfAttestation,
+        constraint = attester_account.is_active @ ErrorCode::AttesterNotActive,
+        constraint = attester_account.bond_lamports >= MIN_ATTESTER_BOND
+            @ ErrorCode::InsufficientAttesterBond
+    )]
+    pub attester_account: Account<'info, AttesterAccount>,
+
+    /// CHECK: Agent's attester allowlist PDA; only enforced if initialized.
+    #[account(
+        seeds = [b"attester_allowlist", agent.key().as_ref()],
+        bump
+    )]
+    pub attester_allowlist: UncheckedAccount<'info>,
+
+    pub attester: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleAttestationRound<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        constraint = !agent.is_retired @ ErrorCode::AgentRetired,
+        constraint = !agent.is_frozen @ ErrorCode::AgentFrozen
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(mut, has_one = agent)]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    #[account(
+        mut,
+        seeds = [b"round", memory_log.key().as_ref()],
+        bump = round.bump
+    )]
+    pub round: Account<'info, AttestationRound>,
+
+    #[account(
+        init,
+        payer = attester,
+        space = Attestation::LEN,
+        seeds = [b"attest", memory_log.key().as_ref()],
+        bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// The settling voter's registration; its bond backs the result.
+    #[account(
+        mut,
+        seeds = [b"attester", attester.key().as_ref()],
+        bump = attester_account.bump,
+        constraint = attester_account.is_active @ ErrorCode::AttesterNotActive,
+        constraint = attester_account.bond_lamports >= MIN_ATTESTER_BOND
+            @ ErrorCode::InsufficientAttesterBond
+    )]
+    pub attester_account: Account<'info, AttesterAccount>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(mut)]
+    pub attester: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"domain_rep",
+            agent.key().as_ref(),
+            memory_log.domain.as_bytes()
+        ],
+        bump = domain_reputation.bump
+    )]
+    pub domain_reputation: Option<Account<'info, DomainReputation>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation_history", agent.key().as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Option<Account<'info, ReputationHistory>>,
+}
+
+#[derive(Accounts)]
+pub struct CancelAttestationRound<'info> {
+    #[account(mut)]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    #[account(
+        mut,
+        close = opener,
+        seeds = [b"round", memory_log.key().as_ref()],
+        bump = round.bump,
+        has_one = memory_log,
+        has_one = opener
+    )]
+    pub round: Account<'info, AttestationRound>,
+
+    /// CHECK: Round opener; receives the rent.
+    #[account(mut)]
+    pub opener: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(domain: String)]
+pub struct InitializeDomainReputation<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = DomainReputation::LEN,
+        seeds = [b"domain_rep", agent.key().as_ref(), domain.as_bytes()],
+        bump
+    )]
+    pub domain_reputation: Account<'info, DomainReputation>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeReputationHistory<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = ReputationHistory::LEN,
+        seeds = [b"reputation_history", agent.key().as_ref()],
+        bump
+    )]
+    pub reputation_history: Account<'info, ReputationHistory>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetReputationAt<'info> {
+    #[account(
+        seeds = [b"reputation_history", reputation_history.agent.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Account<'info, ReputationHistory>,
+}
+
+#[derive(Accounts)]
+pub struct GetReputationCheckpoint<'info> {
+    #[account(
+        seeds = [b"reputation_history", reputation_history.agent.as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Account<'info, ReputationHistory>,
+}
+
+#[derive(Accounts)]
+pub struct RequireReputation<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshReputation<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation_history", agent.key().as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Option<Account<'info, ReputationHistory>>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeAttestation<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(mut, has_one = agent)]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    #[account(
+        mut,
+        seeds = [b"attest", memory_log.key().as_ref()],
+        bump = attestation.bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"domain_rep",
+            agent.key().as_ref(),
+            memory_log.domain.as_bytes()
+        ],
+        bump = domain_reputation.bump
+    )]
+    pub domain_reputation: Option<Account<'info, DomainReputation>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation_history", agent.key().as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Option<Account<'info, ReputationHistory>>,
+}
+
+#[derive(Accounts)]
+pub struct DisputeAttestation<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(has_one = agent)]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    #[account(
+        mut,
+        seeds = [b"attest", memory_log.key().as_ref()],
+        bump = attestation.bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Dispute::LEN,
+        seeds = [b"dispute", attestation.key().as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = arbiter
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(mut, has_one = agent)]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    #[account(
+        mut,
+        seeds = [b"attest", memory_log.key().as_ref()],
+        bump = attestation.bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        mut,
+        seeds = [b"dispute", attestation.key().as_ref()],
+        bump = dispute.bump,
+        has_one = attestation
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    pub arbiter: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"domain_rep",
+            agent.key().as_ref(),
+            memory_log.domain.as_bytes()
+        ],
+        bump = domain_reputation.bump
+    )]
+    pub domain_reputation: Option<Account<'info, DomainReputation>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation_history", agent.key().as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Option<Account<'info, ReputationHistory>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePlatformConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct ReserveAgentName<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = NameReservation::LEN,
+        seeds = [b"name_reservation", name.as_bytes()],
+        bump
+    )]
+    pub name_reservation: Account<'info, NameReservation>,
+
+    /// CHECK: Agent PDA for `name`; must not be registered yet.
+    #[account(
+        seeds = [b"agent", name.as_bytes()],
+        bump
+    )]
+    pub agent: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// CHECK: Platform treasury; receives the reservation fee.
+    #[account(mut, address = platform_config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RenewNameReservation<'info> {
+    #[account(
+        mut,
+        seeds = [b"name_reservation", name_reservation.name.as_bytes()],
+        bump = name_reservation.bump,
+        has_one = owner
+    )]
+    pub name_reservation: Account<'info, NameReservation>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// CHECK: Platform treasury; receives the reservation fee.
+    #[account(mut, address = platform_config.treasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseNameReservation<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"name_reservation", name_reservation.name.as_bytes()],
+        bump = name_reservation.bump,
+        has_one = owner
+    )]
+    pub name_reservation: Account<'info, NameReservation>,
+
+    /// CHECK: Former owner; receives the rent.
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigratePlatformConfig<'info> {
+    /// CHECK: Legacy platform config; owner, discriminator, layout and
+    /// authority are verified in the handler before it is rewritten.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(attester: Pubkey)]
+pub struct RegisterAttester<'info> {
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = AttesterAccount::LEN,
+        seeds = [b"attester", attester.as_ref()],
+        bump
+    )]
+    pub attester_account: Account<'info, AttesterAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = AttesterVault::LEN,
+        seeds = [b"attester_vault", attester.as_ref()],
+        bump
+    )]
+    pub attester_vault: Account<'info, AttesterVault>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LinkAttesterAgent<'info> {
+    #[account(
+        mut,
+        seeds = [b"attester", attester.key().as_ref()],
+        bump = attester_account.bump,
+        constraint = attester_account.agent.is_none()
+            @ ErrorCode::AttesterAgentAlreadyLinked
+    )]
+    pub attester_account: Account<'info, AttesterAccount>,
+
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        constraint = agent.authority == attester.key()
+            @ ErrorCode::AttesterAgentMismatch
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    pub attester: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DepositAttesterBond<'info> {
+    #[account(
+        mut,
+        seeds = [b"attester", attester.key().as_ref()],
+        bump = attester_account.bump
+    )]
+    pub attester_account: Account<'info, AttesterAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"attester_vault", attester.key().as_ref()],
+        bump = attester_vault.bump
+    )]
+    pub attester_vault: Account<'info, AttesterVault>,
+
+    #[account(mut)]
+    pub attester: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawAttesterBond<'info> {
+    #[account(
+        mut,
+        seeds = [b"attester", attester.key().as_ref()],
+        bump = attester_account.bump
+    )]
+    pub attester_account: Account<'info, AttesterAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"attester_vault", attester.key().as_ref()],
+        bump = attester_vault.bump
+    )]
+    pub attester_vault: Account<'info, AttesterVault>,
+
+    #[account(mut)]
+    pub attester: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ChallengeAttestation<'info> {
+    #[account(
+        constraint = attestation.status != AttestationStatus::Rejected
+            @ ErrorCode::AttestationRejected,
+        constraint = attestation.status != AttestationStatus::Disputed
+            @ ErrorCode::DisputeStillOpen
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        mut,
+        seeds = [b"attester", attestation.attester.as_ref()],
+        bump = attester_account.bump
+    )]
+    pub attester_account: Account<'info, AttesterAccount>,
+
+    #[account(
+        init,
+        payer = challenger,
+        space = Challenge::LEN,
+        seeds = [b"challenge", attestation.key().as_ref()],
+        bump
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(mut)]
+    pub challenger: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveChallenge<'info> {
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority,
+        has_one = treasury
+    )]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+
+    #[account(
+        mut,
+        close = challenger,
+        seeds = [b"challenge", attestation.key().as_ref()],
+        bump = challenge.bump,
+        has_one = attestation,
+        has_one = challenger
+    )]
+    pub challenge: Box<Account<'info, Challenge>>,
+
+    #[account(mut, has_one = memory_log)]
+    pub attestation: Box<Account<'info, Attestation>>,
+
+    #[account(has_one = agent)]
+    pub memory_log: Box<Account<'info, MemoryLog>>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Box<Account<'info, AgentAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"attester", attestation.attester.as_ref()],
+        bump = attester_account.bump
+    )]
+    pub attester_account: Box<Account<'info, AttesterAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"attester_vault", attestation.attester.as_ref()],
+        bump = attester_vault.bump
+    )]
+    pub attester_vault: Box<Account<'info, AttesterVault>>,
+
+    /// CHECK: Attester wallet (receives the challenger's escrow if rejected)
+    #[account(mut, address = attestation.attester)]
+    pub attester_wallet: AccountInfo<'info>,
+
+    /// CHECK: Challenger wallet (receives slash share and challenge rent)
+    #[account(mut)]
+    pub challenger: AccountInfo<'info>,
+
+    /// CHECK: Treasury wallet
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"domain_rep",
+            agent.key().as_ref(),
+            memory_log.domain.as_bytes()
+        ],
+        bump = domain_reputation.bump
+    )]
+    pub domain_reputation: Option<Box<Account<'info, DomainReputation>>>,
+
+    #[account(
+        mut,
+        seeds = [b"reputation_history", agent.key().as_ref()],
+        bump = reputation_history.bump
+    )]
+    pub reputation_history: Option<Box<Account<'info, ReputationHistory>>>,
+}
+
+#[derive(Accounts)]
+pub struct SetAttesterActive<'info> {
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"attester", attester_account.attester.as_ref()],
+        bump = attester_account.bump
+    )]
+    pub attester_account: Account<'info, AttesterAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeAttesterAllowlist<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = AttesterAllowlist::LEN,
+        seeds = [b"attester_allowlist", agent.key().as_ref()],
+        bump
+    )]
+    pub attester_allowlist: Account<'info, AttesterAllowlist>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAttesterAllowlist<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"attester_allowlist", agent.key().as_ref()],
+        bump = attester_allowlist.bump
+    )]
+    pub attester_allowlist: Account<'info, AttesterAllowlist>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAttesterAllowlist<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"attester_allowlist", agent.key().as_ref()],
+        bump = attester_allowlist.bump
+    )]
+    pub attester_allowlist: Account<'info, AttesterAllowlist>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAgent<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority,
+        constraint = !agent.is_retired @ ErrorCode::AgentRetired,
+        constraint = !agent.is_frozen @ ErrorCode::AgentFrozen
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ModerateAgent<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        has_one = authority
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AppealFreeze<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseMemoryLog<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        has_one = agent
+    )]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    /// CHECK: The log's attestation PDA; must already be closed.
+    #[account(
+        seeds = [b"attest", memory_log.key().as_ref()],
+        bump
+    )]
+    pub attestation: UncheckedAccount<'info>,
+
+    /// CHECK: The log's attestation round PDA; must already be closed.
+    #[account(
+        seeds = [b"round", memory_log.key().as_ref()],
+        bump
+    )]
+    pub round: UncheckedAccount<'info>,
+
+    /// CHECK: Receives the rent; checked against the agent authority.
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAttestation<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(has_one = agent)]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    #[account(
+        mut,
+        close = attester,
+        seeds = [b"attest", memory_log.key().as_ref()],
+        bump = attestation.bump,
+        has_one = attester
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// CHECK: The attestation's challenge PDA; must not be open.
+    #[account(
+        seeds = [b"challenge", attestation.key().as_ref()],
+        bump
+    )]
+    pub challenge: UncheckedAccount<'info>,
+
+    /// CHECK: Attester who paid for the attestation; receives the rent.
+    #[account(mut)]
+    pub attester: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDecisionBatch<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        has_one = agent
+    )]
+    pub batch: Account<'info, DecisionBatch>,
+
+    /// CHECK: Receives the rent; checked against the agent authority.
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAttestationRound<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        close = opener,
+        has_one = agent,
+        has_one = opener
+    )]
+    pub round: Account<'info, AttestationRound>,
+
+    /// CHECK: Round opener; receives the rent.
+    #[account(mut)]
+    pub opener: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDispute<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        has_one = agent
+    )]
+    pub dispute: Account<'info, Dispute>,
+
+    /// CHECK: Receives the rent; checked against the agent authority.
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseChallenge<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(has_one = agent)]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    #[account(
+        seeds = [b"attest", memory_log.key().as_ref()],
+        bump = attestation.bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    #[account(
+        mut,
+        close = challenger,
+        seeds = [b"challenge", attestation.key().as_ref()],
+        bump = challenge.bump,
+        has_one = attestation,
+        has_one = challenger
+    )]
+    pub challenge: Account<'info, Challenge>,
+
+    #[account(
+        mut,
+        seeds = [b"attester", attestation.attester.as_ref()],
+        bump = attester_account.bump
+    )]
+    pub attester_account: Account<'info, AttesterAccount>,
+
+    /// CHECK: Challenger; receives the escrow and rent.
+    #[account(mut)]
+    pub challenger: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDelegate<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        has_one = agent
+    )]
+    pub delegate: Account<'info, Delegate>,
+
+    /// CHECK: Receives the rent; checked against the agent authority.
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseDomainReputation<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        has_one = agent
+    )]
+    pub domain_reputation: Account<'info, DomainReputation>,
+
+    /// CHECK: Receives the rent; checked against the agent authority.
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseReputationHistory<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        has_one = agent
+    )]
+    pub reputation_history: Account<'info, ReputationHistory>,
+
+    /// CHECK: Receives the rent; checked against the agent authority.
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseAgentVersion<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        has_one = agent
+    )]
+    pub agent_version: Account<'info, AgentVersion>,
+
+    /// CHECK: Receives the rent; checked against the agent authority.
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseModulePurchase<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        has_one = agent
+    )]
+    pub module_purchase: Account<'info, ModulePurchase>,
+
+    /// CHECK: Receives the rent; checked against the agent authority.
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(profile: AgentProfile)]
+pub struct UpdateAgentProfile<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority,
+        realloc = AgentAccount::space_for(&profile),
+        realloc::payer = authority,
+        realloc::zero = false
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct GrantDelegate<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Delegate::LEN,
+        seeds = [b"delegate", agent.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub delegate: Account<'info, Delegate>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeDelegate<'info> {
+    #[account(
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            b"delegate",
+            agent.key().as_ref(),
+            delegate.delegate.as_ref()
+        ],
+        bump = delegate.bump
+    )]
+    pub delegate: Account<'info, Delegate>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAgentAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRecoveryAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    pub authority: Signer<'info>,
+
+    /// The current recovery key; required once one is set.
+    pub recovery_authority: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthorityTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        constraint = agent.pending_authority == Some(new_authority.key())
+            @ ErrorCode::NotPendingAuthority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    pub new_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecoverAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        constraint = agent.recovery_authority == Some(recovery_authority.key())
+            @ ErrorCode::NotRecoveryAuthority
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    pub recovery_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct EndorseAgent<'info> {
+    #[account(
+        seeds = [b"agent", endorser.agent_id.as_bytes()],
+        bump = endorser.bump,
+        has_one = authority,
+        constraint = !endorser.is_frozen @ ErrorCode::AgentFrozen,
+        constraint = !endorser.is_retired @ ErrorCode::AgentRetired
+    )]
+    pub endorser: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", endorsee.agent_id.as_bytes()],
+        bump = endorsee.bump,
+        constraint = endorsee.key() != endorser.key() @ ErrorCode::SelfEndorsement,
+        constraint = !endorsee.is_retired @ ErrorCode::AgentRetired
+    )]
+    pub endorsee: Account<'info, AgentAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Endorsement::LEN,
+        seeds = [
+            b"endorsement",
+            endorser.key().as_ref(),
+            endorsee.key().as_ref()
+        ],
+        bump
+    )]
+    pub endorsement: Account<'info, Endorsement>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshEndorsement<'info> {
+    #[account(
+        seeds = [b"agent", endorser.agent_id.as_bytes()],
+        bump = endorser.bump
+    )]
+    pub endorser: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", endorsee.agent_id.as_bytes()],
+        bump = endorsee.bump
+    )]
+    pub endorsee: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"endorsement",
+            endorser.key().as_ref(),
+            endorsee.key().as_ref()
+        ],
+        bump = endorsement.bump
+    )]
+    pub endorsement: Account<'info, Endorsement>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeEndorsement<'info> {
+    #[account(
+        seeds = [b"agent", endorser.agent_id.as_bytes()],
+        bump = endorser.bump,
+        has_one = authority
+    )]
+    pub endorser: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", endorsee.agent_id.as_bytes()],
+        bump = endorsee.bump
+    )]
+    pub endorsee: Account<'info, AgentAccount>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            b"endorsement",
+            endorser.key().as_ref(),
+            endorsee.key().as_ref()
+        ],
+        bump = endorsement.bump
+    )]
+    pub endorsement: Account<'info, Endorsement>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(org_id: String)]
+pub struct InitializeOrganization<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = Organization::LEN,
+        seeds = [b"organization", org_id.as_bytes()],
+        bump
+    )]
+    pub organization: Account<'info, Organization>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateOrganization<'info> {
+    #[account(
+        mut,
+        seeds = [b"organization", organization.org_id.as_bytes()],
+        bump = organization.bump,
+        constraint = organization.admins.contains(&admin.key())
+            @ ErrorCode::NotOrganizationAdmin
+    )]
+    pub organization: Account<'info, Organization>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct LeaveOrganization<'info> {
+    #[account(
+        mut,
+        seeds = [b"organization", organization.org_id.as_bytes()],
+        bump = organization.bump
+    )]
+    pub organization: Account<'info, Organization>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority,
+        constraint = agent.organization == Some(organization.key())
+            @ ErrorCode::NotOrganizationMember
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromOrganization<'info> {
+    #[account(
+        mut,
+        seeds = [b"organization", organization.org_id.as_bytes()],
+        bump = organization.bump,
+        constraint = organization.admins.contains(&admin.key())
+            @ ErrorCode::NotOrganizationAdmin
+    )]
+    pub organization: Account<'info, Organization>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        constraint = agent.organization == Some(organization.key())
+            @ ErrorCode::NotOrganizationMember
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundOrganization<'info> {
+    #[account(
+        mut,
+        seeds = [b"organization", organization.org_id.as_bytes()],
+        bump = organization.bump
+    )]
+    pub organization: Account<'info, Organization>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(version: u32)]
+pub struct ActivateAgentVersion<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        has_one = authority,
+        constraint = !agent.is_retired @ ErrorCode::AgentRetired
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = AgentVersion::LEN,
+        seeds = [b"agent_version", agent.key().as_ref(), &version.to_le_bytes()],
+        bump
+    )]
+    pub agent_version: Account<'info, AgentVersion>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TallyVersionOutcome<'info> {
+    #[account(mut)]
+    pub memory_log: Account<'info, MemoryLog>,
+
+    #[account(
+        seeds = [b"attest", memory_log.key().as_ref()],
+        bump = attestation.bump
+    )]
+    pub attestation: Account<'info, Attestation>,
+
+    /// CHECK: Challenge PDA for the attestation; must not exist.
+    #[account(
+        seeds = [b"challenge", attestation.key().as_ref()],
+        bump
+    )]
+    pub challenge: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"agent_version",
+            memory_log.agent.as_ref(),
+            &memory_log.version.to_le_bytes()
+        ],
+        bump = agent_version.bump
+    )]
+    pub agent_version: Account<'info, AgentVersion>,
+}
+
+#[derive(Accounts)]
+pub struct SyncOrganizationStats<'info> {
+    #[account(
+        mut,
+        seeds = [b"organization", organization.org_id.as_bytes()],
+        bump = organization.bump
+    )]
+    pub organization: Account<'info, Organization>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        constraint = agent.organization == Some(organization.key())
+            @ ErrorCode::NotOrganizationMember
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePlatform<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = PlatformConfig::LEN,
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(module_id: String)]
+pub struct RegisterModule<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + 68 + 32 + 8 + 2 + 8 + 8 + 132 + 1 + 1,
+        seeds = [b"module", module_id.as_bytes()],
+        bump
+    )]
+    pub module_metadata: Account<'info, ModuleMetadata>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PurchaseModule<'info> {
+    #[account(
+        mut,
+        seeds = [b"module", module_metadata.module_id.as_bytes()],
+        bump = module_metadata.bump
+    )]
+    pub module_metadata: Account<'info, ModuleMetadata>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + 32 + 32 + 8 + 8 + 1,
+        seeds = [b"purchase", agent.key().as_ref(), module_metadata.key().as_ref()],
+        bump
+    )]
+    pub module_purchase: Account<'info, ModulePurchase>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent.agent_id.as_bytes()],
+        bump = agent.bump,
+        constraint = !agent.is_retired @ ErrorCode::AgentRetired,
+        constraint = !agent.is_frozen @ ErrorCode::AgentFrozen
+    )]
+    pub agent: Account<'info, AgentAccount>,
+
+    /// The agent authority, or a session key whose `Delegate` grant allows
+    /// purchases.
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// CHECK: Treasury wallet
+    #[account(mut)]
+    pub treasury: AccountInfo<'info>,
+
+    /// CHECK: Creator wallet
+    #[account(mut)]
+    pub creator_wallet: AccountInfo<'info>,
+
+    /// CHECK: Optional referrer wallet
+    #[account(mut)]
+    pub referrer_wallet: Option<AccountInfo<'info>>,
+
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds = [b"delegate", agent.key().as_ref(), buyer.key().as_ref()],
+        bump = delegate.bump
+    )]
+    pub delegate: Option<Account<'info, Delegate>>,
+
+    /// Pay from this organisation's budget instead of the buyer's wallet.
+    #[account(
+        mut,
+        seeds = [b"organization", organization.org_id.as_bytes()],
+        bump = organization.bump,
+        constraint = agent.organization == Some(organization.key())
+            @ ErrorCode::NotOrganizationMember
+    )]
+    pub organization: Option<Account<'info, Organization>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateModulePricing<'info> {
+    #[account(
+        mut,
+        seeds = [b"module", module_metadata.module_id.as_bytes()],
+        bump = module_metadata.bump,
+        has_one = creator
+    )]
+    pub module_metadata: Account<'info, ModuleMetadata>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DeactivateModule<'info> {
+    #[account(
+        mut,
+        seeds = [b"module", module_metadata.module_id.as_bytes()],
+        bump = module_metadata.bump,
+        has_one = creator
+    )]
+    pub module_metadata: Account<'info, ModuleMetadata>,
+
+    pub creator: Signer<'info>,
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+#[event]
+pub struct DecisionLogged {
+    pub agent: Pubkey,
+    pub memory_log: Pubkey,
+    pub sequence: u64,
+    pub merkle_root: [u8; 32],
+    pub chain_head: [u8; 32],
+    pub is_commitment: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DecisionRevealed {
+    pub agent: Pubkey,
+    pub memory_log: Pubkey,
+    pub sequence: u64,
+    pub input_data: String,
+    pub logic_data: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RevealMissed {
+    pub agent: Pubkey,
+    pub memory_log: Pubkey,
+    pub sequence: u64,
+    pub reveal_deadline: i64,
+}
+
+#[event]
+pub struct DecisionBatchLogged {
+    pub agent: Pubkey,
+    pub batch: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub leaf_count: u32,
+    pub first_sequence: u64,
+    pub last_sequence: u64,
+    pub chain_head: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DecisionVerified {
+    pub agent: Pubkey,
+    pub root_account: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub leaf: [u8; 32],
+    pub sequence: u64,
+}
+
+#[event]
+pub struct LogChainVerified {
+    pub agent: Pubkey,
+    pub first_log: Pubkey,
+    pub last_log: Pubkey,
+    pub first_sequence: u64,
+    pub last_sequence: u64,
+    pub reaches_head: bool,
+}
+
+#[event]
+pub struct MemoryLogMigrated {
+    pub agent: Pubkey,
+    pub memory_log: Pubkey,
+    pub legacy_timestamp: i64,
+    pub sequence: u64,
+}
+
+#[event]
+pub struct OutcomeAttested {
+    pub agent: Pubkey,
+    pub memory_log: Pubkey,
+    pub attestation: Pubkey,
+    pub success: bool,
+    pub score_delta: i64,
+    pub dispute_deadline: i64,
+}
+
+#[event]
+pub struct AttestationRoundOpened {
+    pub agent: Pubkey,
+    pub memory_log: Pubkey,
+    pub round: Pubkey,
+    pub quorum: u8,
+}
+
+#[event]
+pub struct RoundVoteSubmitted {
+    pub round: Pubkey,
+    pub attester: Pubkey,
+    pub success: bool,
+    pub score_delta: i64,
+}
+
+#[event]
+pub struct AttestationRoundFinalized {
+    pub agent: Pubkey,
+    pub round: Pubkey,
+    pub attestation: Pubkey,
+    pub success: bool,
+    pub score_delta: i64,
+    pub votes: u8,
+    pub dispute_deadline: i64,
+}
+
+#[event]
+pub struct AttestationRoundCancelled {
+    pub agent: Pubkey,
+    pub memory_log: Pubkey,
+    pub round: Pubkey,
+    pub votes: u8,
+}
+
+#[event]
+pub struct ReputationRefreshed {
+    pub agent: Pubkey,
+    pub reputation: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScoringModelUpdated {
+    pub scoring_model: ScoringModel,
+}
+
+#[event]
+pub struct DomainReputationUpdated {
+    pub agent: Pubkey,
+    pub domain: String,
+    pub reputation: i64,
+}
+
+#[event]
+pub struct AttestationFinalized {
+    pub agent: Pubkey,
+    pub attestation: Pubkey,
+    pub success: bool,
+    pub score_delta: i64,
+    pub new_reputation: i64,
+}
+
+#[event]
+pub struct AttestationDisputed {
+    pub agent: Pubkey,
+    pub attestation: Pubkey,
+    pub evidence_hash: [u8; 32],
+}
+
+#[event]
+pub struct DisputeResolved {
+    pub agent: Pubkey,
+    pub attestation: Pubkey,
+    pub attestation_upheld: bool,
+    pub new_reputation: i64,
+}
+
+#[event]
+pub struct AgentNameReserved {
+    pub name: String,
+    pub owner: Pubkey,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct AgentNameReleased {
+    pub name: String,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct AgentClosed {
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub reputation: i64,
+    pub total_logs: u64,
+    pub total_attestations: u64,
+    pub success_count: u64,
+    pub failure_count: u64,
+    pub chain_head: [u8; 32],
+    pub last_log: Pubkey,
+    pub retired_at: i64,
+}
+
+#[event]
+pub struct AgentModerated {
+    pub agent: Pubkey,
+    pub frozen: bool,
+    pub reason: u16,
+    pub evidence_hash: [u8; 32],
+}
+
+#[event]
+pub struct FreezeAppealed {
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub statement_hash: [u8; 32],
+    pub moderation_reason: u16,
+}
+
+#[event]
+pub struct AgentProfileUpdated {
+    pub agent: Pubkey,
+    pub metadata_uri: String,
+    pub metadata_hash: [u8; 32],
+    pub capabilities: u64,
+}
+
+#[event]
+pub struct DelegateGranted {
+    pub agent: Pubkey,
+    pub delegate: Pubkey,
+    pub expires_at: i64,
+    pub max_logs: u64,
+    pub can_purchase: bool,
+}
+
+#[event]
+pub struct DelegateRevoked {
+    pub agent: Pubkey,
+    pub delegate: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferProposed {
+    pub agent: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+#[event]
+pub struct AuthorityTransferred {
+    pub agent: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub via_recovery: bool,
+}
+
+#[event]
+pub struct RecoveryAuthorityChanged {
+    pub agent: Pubkey,
+    pub recovery_authority: Option<Pubkey>,
+}
+
+#[event]
+pub struct AgentEndorsed {
+    pub endorser: Pubkey,
+    pub endorsee: Pubkey,
+    pub weight: u64,
+    pub endorsement_score: u64,
+}
+
+#[event]
+pub struct EndorsementRefreshed {
+    pub endorser: Pubkey,
+    pub endorsee: Pubkey,
+    pub weight: u64,
+    pub endorsement_score: u64,
+}
+
+#[event]
+pub struct EndorsementRevoked {
+    pub endorser: Pubkey,
+    pub endorsee: Pubkey,
+    pub weight: u64,
+    pub endorsement_score: u64,
+}
+
+#[event]
+pub struct AgentVersionActivated {
+    pub agent: Pubkey,
+    pub version: u32,
+    pub model_hash: [u8; 32],
+    pub config_hash: [u8; 32],
+    pub activated_at: i64,
+}
+
+#[event]
+pub struct AgentVersionOutcomeTallied {
+    pub agent: Pubkey,
+    pub version: u32,
+    pub memory_log: Pubkey,
+    pub success: bool,
+    pub reputation: i64,
+}
+
+#[event]
+pub struct OrganizationBudgetChanged {
+    pub organization: Pubkey,
+    pub budget: u64,
+}
+
+#[event]
+pub struct OrganizationMemberRemoved {
+    pub organization: Pubkey,
+    pub agent: Pubkey,
+    pub agent_count: u32,
+}
+
+#[event]
+pub struct OrganizationStatsUpdated {
+    pub organization: Pubkey,
+    pub agent_count: u32,
+    pub total_reputation: i64,
+    pub total_logs: u64,
+    pub total_spent: u64,
+}
+
+#[event]
+pub struct AttesterStatusChanged {
+    pub attester: Pubkey,
+    pub is_active: bool,
+}
+
+#[event]
+pub struct AttesterAllowlistUpdated {
+    pub agent: Pubkey,
+    pub attesters: Vec<Pubkey>,
 }
 
-#[account]
-pub struct Attestation {
-    pub memory_log: Pubkey,
-    pub outcome_hash: [u8; 32],
-    pub success: bool,
-    pub score_delta: i64,
-    pub timestamp: i64,
-    pub bump: u8,
+#[event]
+pub struct AttesterBondChanged {
+    pub attester: Pubkey,
+    pub bond_lamports: u64,
 }
 
-#[account]
-pub struct ModuleMetadata {
+#[event]
+pub struct AttestationChallenged {
+    pub attestation: Pubkey,
+    pub challenger: Pubkey,
+    pub evidence_hash: [u8; 32],
+}
+
+#[event]
+pub struct ChallengeResolved {
+    pub attestation: Pubkey,
+    pub challenger: Pubkey,
+    pub upheld: bool,
+    pub slashed_lamports: u64,
+    pub new_reputation: i64,
+}
+
+#[event]
+pub struct ModuleRegistered {
+    pub module: Pubkey,
     pub module_id: String,
     pub creator: Pubkey,
     pub price_lamports: u64,
-    pub royalty_bps: u16,
-    pub total_sales: u64,
-    pub total_revenue: u64,
     pub ipfs_hash: String,
-    pub is_active: bool,
-    pub bump: u8,
 }
 
-#[account]
-pub struct ModulePurchase {
-    pub agent: Pubkey,
+#[event]
+pub struct ModulePurchased {
     pub module: Pubkey,
-    pub purchased_at: i64,
+    pub agent: Pubkey,
     pub price_paid: u64,
-    pub bump: u8,
-}
-
-#[account]
-pub struct PlatformConfig {
-    pub authority: Pubkey,
-    pub treasury: Pubkey,
-    pub platform_fee_bps: u16,
-    pub referral_fee_bps: u16,
-    pub bump: u8,
+    pub platform_fee: u64,
+    pub creator_royalty: u64,
+    pub referral_fee: u64,
 }
 
 // ============================================================================
-// Context Structures
+// Helpers
 // ============================================================================
 
-#[derive(Accounts)]
-#[instruction(agent_id: String)]
-pub struct InitializeAgent<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + 68 + 32 + 8 + 8 + 8 + 1,
-        seeds = [b"agent", agent_id.as_bytes()],
-        bump
-    )]
-    pub agent: Account<'info, AgentAccount>,
+/// Salted commitment accepted by `log_decision_commitment`:
+/// `keccak(salt || data)`. Salts should be 32 random bytes kept secret until
+/// the decision is revealed.
+pub fn decision_commitment(salt: &[u8; 32], data: &[u8]) -> [u8; 32] {
+    keccak::hashv(&[salt, data]).to_bytes()
+}
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+/// Require the agent to be retired and past `RETENTION_PERIOD_SECS`.
+fn check_retention_elapsed(agent: &AgentAccount) -> Result<()> {
+    require!(agent.is_retired, ErrorCode::AgentNotRetired);
+    require!(
+        Clock::get()?.unix_timestamp
+            >= agent.retired_at + RETENTION_PERIOD_SECS,
+        ErrorCode::RetentionPeriodActive
+    );
+    Ok(())
+}
 
-    pub system_program: Program<'info, System>,
+/// What a session key is trying to do on an agent's behalf.
+#[derive(Clone, Copy, PartialEq)]
+enum DelegatedAction {
+    LogDecision,
+    PurchaseModule,
 }
 
-#[derive(Accounts)]
-pub struct LogDecision<'info> {
-    #[account(
-        mut,
-        seeds = [b"agent", agent.agent_id.as_bytes()],
-        bump = agent.bump,
-        has_one = authority
-    )]
-    pub agent: Account<'info, AgentAccount>,
+/// Accept `signer` if it is the agent authority, or if `delegate` is a live
+/// grant to it covering `action` made under the current authority. A
+/// delegated log uses up one of the grant's `max_logs`.
+fn authorize_delegated_signer(
+    agent: &AgentAccount,
+    signer: &Pubkey,
+    delegate: Option<&mut Account<Delegate>>,
+    action: DelegatedAction,
+    now: i64,
+) -> Result<()> {
+    let Some(delegate) = delegate else {
+        require_keys_eq!(
+            *signer,
+            agent.authority,
+            ErrorCode::UnauthorizedSigner
+        );
+        return Ok(());
+    };
 
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 32 + 32 + 32 + 8 + 1 + 1,
-        seeds = [b"memory", agent.key().as_ref(), &Clock::get()?.unix_timestamp.to_le_bytes()],
-        bump
-    )]
-    pub memory_log: Account<'info, MemoryLog>,
+    require_keys_eq!(*signer, delegate.delegate, ErrorCode::UnauthorizedSigner);
+    require!(
+        delegate.authority_epoch == agent.authority_epoch,
+        ErrorCode::StaleDelegate
+    );
+    require!(now < delegate.expires_at, ErrorCode::DelegateExpired);
+    match action {
+        DelegatedAction::LogDecision => {
+            require!(
+                delegate.logs_used < delegate.max_logs,
+                ErrorCode::DelegateLogLimitReached
+            );
+            delegate.logs_used += 1;
+        }
+        DelegatedAction::PurchaseModule => {
+            require!(delegate.can_purchase, ErrorCode::DelegateCannotPurchase);
+        }
+    }
+    Ok(())
+}
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+/// Write a new decision into `accounts.memory_log` and advance the agent's
+/// sequence counter and chain head.
+fn record_decision(
+    accounts: &mut LogDecision,
+    bump: u8,
+    input_hash: [u8; 32],
+    logic_hash: [u8; 32],
+    domain: String,
+    is_commitment: bool,
+) -> Result<()> {
+    validate_domain(&domain)?;
 
-    pub system_program: Program<'info, System>,
-}
+    let clock = Clock::get()?;
+    authorize_delegated_signer(
+        &accounts.agent,
+        &accounts.authority.key(),
+        accounts.delegate.as_mut(),
+        DelegatedAction::LogDecision,
+        clock.unix_timestamp,
+    )?;
 
-#[derive(Accounts)]
-pub struct AttestOutcome<'info> {
-    #[account(
-        mut,
-        seeds = [b"agent", agent.agent_id.as_bytes()],
-        bump = agent.bump
-    )]
-    pub agent: Account<'info, AgentAccount>,
+    let agent = &mut accounts.agent;
+    let memory_log = &mut accounts.memory_log;
 
-    #[account(
-        mut,
-        has_one = agent
-    )]
-    pub memory_log: Account<'info, MemoryLog>,
+    let merkle_root = merkle::hash_leaf(&input_hash, &logic_hash);
 
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 32 + 1 + 8 + 8 + 1,
-        seeds = [b"attest", memory_log.key().as_ref()],
-        bump
-    )]
-    pub attestation: Account<'info, Attestation>,
+    memory_log.agent = agent.key();
+    memory_log.sequence = agent.total_logs;
+    memory_log.input_hash = input_hash;
+    memory_log.logic_hash = logic_hash;
+    memory_log.merkle_root = merkle_root;
+    memory_log.timestamp = clock.unix_timestamp;
+    memory_log.is_attested = false;
+    memory_log.is_commitment = is_commitment;
+    memory_log.is_revealed = !is_commitment;
+    memory_log.reveal_after = 0;
+    memory_log.reveal_deadline = 0;
+    memory_log.reveal_missed = false;
+    memory_log.domain = domain;
+    memory_log.prev_log = agent.last_log;
+    memory_log.prev_root = agent.chain_head;
+    memory_log.version = agent.active_version;
+    memory_log.version_tallied = false;
+    memory_log.bump = bump;
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    agent.chain_head = chain::link(
+        &memory_log.prev_root,
+        &merkle_root,
+        memory_log.sequence,
+        memory_log.sequence,
+    );
+    agent.last_log = memory_log.key();
+    agent.total_logs += 1;
 
-    pub system_program: Program<'info, System>,
+    emit!(DecisionLogged {
+        agent: agent.key(),
+        memory_log: memory_log.key(),
+        sequence: memory_log.sequence,
+        merkle_root,
+        chain_head: agent.chain_head,
+        is_commitment,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Decision logged for agent: {} (seq={})",
+        agent.agent_id,
+        memory_log.sequence
+    );
+    Ok(())
 }
 
-#[derive(Accounts)]
-pub struct InitializePlatform<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 32 + 2 + 2 + 1,
-        seeds = [b"platform_config"],
-        bump
-    )]
-    pub platform_config: Account<'info, PlatformConfig>,
+/// Apply a pending or disputed attestation's `score_delta` to its agent.
+/// Scoring is held while the agent is frozen. A sealed decision whose reveal deadline passed while the attestation was
+/// pending is penalised here, as of `now`.
+fn finalize_attestation_outcome(
+    agent: &mut Account<AgentAccount>,
+    domain_reputation: Option<&mut Account<DomainReputation>>,
+    reputation_history: Option<&mut Account<ReputationHistory>>,
+    memory_log: &mut MemoryLog,
+    attestation: &mut Account<Attestation>,
+    config: &PlatformConfig,
+    now: i64,
+) -> Result<()> {
+    require!(!agent.is_frozen, ErrorCode::AgentFrozen);
+    (attestation.success, attestation.score_delta) = memory_log
+        .penalise_missed_reveal(
+            now,
+            attestation.success,
+            attestation.score_delta,
+        );
+    attestation.applied_delta = apply_outcome(
+        agent,
+        domain_reputation_for(memory_log, domain_reputation)?,
+        attestation.success,
+        attestation.score_delta,
+        attestation.attester_reputation,
+        config,
+        now,
+    )?;
+    record_checkpoint(agent, config, reputation_history)?;
+    attestation.status = AttestationStatus::Finalized;
+    agent.total_attestations += 1;
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    emit!(AttestationFinalized {
+        agent: agent.key(),
+        attestation: attestation.key(),
+        success: attestation.success,
+        score_delta: attestation.applied_delta,
+        new_reputation: agent.reputation,
+    });
 
-    pub system_program: Program<'info, System>,
+    msg!(
+        "Attestation finalized for agent: {}, new reputation: {}",
+        agent.agent_id,
+        agent.reputation
+    );
+    Ok(())
 }
 
-#[derive(Accounts)]
-#[instruction(module_id: String)]
-pub struct RegisterModule<'info> {
-    #[account(
-        init,
-        payer = creator,
-        space = 8 + 68 + 32 + 8 + 2 + 8 + 8 + 132 + 1 + 1,
-        seeds = [b"module", module_id.as_bytes()],
-        bump
-    )]
-    pub module_metadata: Account<'info, ModuleMetadata>,
-
-    #[account(mut)]
-    pub creator: Signer<'info>,
+/// Append the agent's current reputation, and the half-life it decays by,
+/// to its history, if it keeps one.
+fn record_checkpoint(
+    agent: &AgentAccount,
+    config: &PlatformConfig,
+    reputation_history: Option<&mut Account<ReputationHistory>>,
+) -> Result<()> {
+    if !agent.has_reputation_history {
+        return Ok(());
+    }
+    let reputation_history =
+        reputation_history.ok_or(ErrorCode::MissingReputationHistory)?;
+    reputation_history.push(ReputationCheckpoint {
+        slot: Clock::get()?.slot,
+        timestamp: agent.last_reputation_update,
+        reputation: agent.reputation,
+        half_life_secs: config.decay_half_life(),
+    });
+    Ok(())
+}
 
-    pub system_program: Program<'info, System>,
+/// The `DomainReputation` an outcome on `memory_log` must also update: none
+/// for an untagged log, and an error if a tagged log's account is missing.
+fn domain_reputation_for<'a, 'info>(
+    memory_log: &MemoryLog,
+    domain_reputation: Option<&'a mut Account<'info, DomainReputation>>,
+) -> Result<Option<&'a mut Account<'info, DomainReputation>>> {
+    if memory_log.domain.is_empty() {
+        return Ok(None);
+    }
+    match domain_reputation {
+        Some(domain_reputation) => Ok(Some(domain_reputation)),
+        None => err!(ErrorCode::MissingDomainReputation),
+    }
 }
 
-#[derive(Accounts)]
-pub struct PurchaseModule<'info> {
-    #[account(
-        mut,
-        seeds = [b"module", module_metadata.module_id.as_bytes()],
-        bump = module_metadata.bump
-    )]
-    pub module_metadata: Account<'info, ModuleMetadata>,
+/// Record an outcome against the agent's global reputation and, for a log
+/// tagged with a domain, against its `DomainReputation` as well (resolved
+/// with `domain_reputation_for`). Returns the delta applied under the
+/// platform's scoring model.
+fn apply_outcome(
+    agent: &mut Account<AgentAccount>,
+    domain_reputation: Option<&mut Account<DomainReputation>>,
+    success: bool,
+    score_delta: i64,
+    attester_reputation: Option<i64>,
+    config: &PlatformConfig,
+    now: i64,
+) -> Result<i64> {
+    let delta = agent.record_outcome(
+        success,
+        score_delta,
+        attester_reputation,
+        config,
+        now,
+    )?;
 
-    #[account(
-        seeds = [b"platform_config"],
-        bump = platform_config.bump
-    )]
-    pub platform_config: Account<'info, PlatformConfig>,
+    if let Some(domain_reputation) = domain_reputation {
+        domain_reputation.record_outcome(success, delta, config, now)?;
 
-    #[account(
-        init,
-        payer = buyer,
-        space = 8 + 32 + 32 + 8 + 8 + 1,
-        seeds = [b"purchase", agent.key().as_ref(), module_metadata.key().as_ref()],
-        bump
-    )]
-    pub module_purchase: Account<'info, ModulePurchase>,
+        emit!(DomainReputationUpdated {
+            agent: agent.key(),
+            domain: domain_reputation.domain.clone(),
+            reputation: domain_reputation.reputation,
+        });
+    }
+    Ok(delta)
+}
 
-    #[account(
-        seeds = [b"agent", agent.agent_id.as_bytes()],
-        bump = agent.bump
-    )]
-    pub agent: Account<'info, AgentAccount>,
+/// Undo an outcome recorded by `apply_outcome`.
+fn reverse_outcome(
+    agent: &mut Account<AgentAccount>,
+    domain_reputation: Option<&mut Account<DomainReputation>>,
+    success: bool,
+    delta: i64,
+    config: &PlatformConfig,
+    now: i64,
+) -> Result<()> {
+    agent.reverse_outcome(success, delta, config, now)?;
 
-    #[account(mut)]
-    pub buyer: Signer<'info>,
+    if let Some(domain_reputation) = domain_reputation {
+        domain_reputation.reverse_outcome(success, delta, config, now)?;
 
-    /// CHECK: Treasury wallet
-    #[account(mut)]
-    pub treasury: AccountInfo<'info>,
+        emit!(DomainReputationUpdated {
+            agent: agent.key(),
+            domain: domain_reputation.domain.clone(),
+            reputation: domain_reputation.reputation,
+        });
+    }
+    Ok(())
+}
 
-    /// CHECK: Creator wallet
-    #[account(mut)]
-    pub creator_wallet: AccountInfo<'info>,
+/// Domains are short lowercase tags (`a-z`, `0-9`, `-`, `_`) so that one
+/// category cannot be split across spellings.
+fn validate_domain(domain: &str) -> Result<()> {
+    require!(domain.len() <= MAX_DOMAIN_LEN, ErrorCode::InvalidDomain);
+    require!(is_canonical_name(domain), ErrorCode::InvalidDomain);
+    Ok(())
+}
 
-    /// CHECK: Optional referrer wallet
-    #[account(mut)]
-    pub referrer_wallet: Option<AccountInfo<'info>>,
+/// Lowercase ASCII letters, digits, `-` and `_` only, so names cannot differ
+/// by case or by look-alike Unicode characters.
+fn is_canonical_name(name: &str) -> bool {
+    name.bytes().all(|b| {
+        b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'_'
+    })
+}
 
-    pub system_program: Program<'info, System>,
+fn validate_agent_id(agent_id: &str) -> Result<()> {
+    require!(
+        agent_id.len() <= MAX_AGENT_ID_LEN,
+        ErrorCode::AgentIdTooLong
+    );
+    require!(
+        !agent_id.is_empty() && is_canonical_name(agent_id),
+        ErrorCode::InvalidAgentId
+    );
+    Ok(())
 }
 
-#[derive(Accounts)]
-pub struct UpdateModulePricing<'info> {
-    #[account(
-        mut,
-        seeds = [b"module", module_metadata.module_id.as_bytes()],
-        bump = module_metadata.bump,
-        has_one = creator
-    )]
-    pub module_metadata: Account<'info, ModuleMetadata>,
+/// Reject `name` if the platform reserves it (unless `authority` is the
+/// platform authority) or someone else holds a live reservation on it.
+fn check_name_available(
+    name: &str,
+    authority: &Pubkey,
+    config: &PlatformConfig,
+    reservation: &UncheckedAccount,
+) -> Result<()> {
+    require!(
+        *authority == config.authority
+            || !config
+                .reserved_names
+                .iter()
+                .any(|reserved| reserved == name),
+        ErrorCode::NameUnavailable
+    );
 
-    pub creator: Signer<'info>,
+    if reservation.data_is_empty() {
+        return Ok(());
+    }
+    let data = reservation.try_borrow_data()?;
+    let reservation = NameReservation::try_deserialize(&mut &data[..])?;
+    require!(
+        reservation.owner == *authority
+            || reservation.expires_at <= Clock::get()?.unix_timestamp,
+        ErrorCode::NameUnavailable
+    );
+    Ok(())
 }
 
-#[derive(Accounts)]
-pub struct DeactivateModule<'info> {
-    #[account(
-        mut,
-        seeds = [b"module", module_metadata.module_id.as_bytes()],
-        bump = module_metadata.bump,
-        has_one = creator
-    )]
-    pub module_metadata: Account<'info, ModuleMetadata>,
+fn pay_name_reservation_fee<'info>(
+    fee: u64,
+    owner: &Signer<'info>,
+    treasury: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    if fee == 0 {
+        return Ok(());
+    }
+    invoke(
+        &system_instruction::transfer(owner.key, treasury.key, fee),
+        &[
+            owner.to_account_info(),
+            treasury.to_account_info(),
+            system_program.to_account_info(),
+        ],
+    )?;
+    Ok(())
+}
 
-    pub creator: Signer<'info>,
+/// Enforce the agent's attester allowlist if it has one. The allowlist PDA is
+/// always passed, so an agent cannot be attested around its own list.
+fn check_attester_allowlist(
+    allowlist: &UncheckedAccount,
+    attester: &Pubkey,
+) -> Result<()> {
+    if allowlist.data_is_empty() {
+        return Ok(());
+    }
+    let data = allowlist.try_borrow_data()?;
+    let allowlist = AttesterAllowlist::try_deserialize(&mut &data[..])?;
+    require!(
+        allowlist.attesters.contains(attester),
+        ErrorCode::AttesterNotAllowed
+    );
+    Ok(())
 }
 
-// ============================================================================
-// Events
-// ============================================================================
+fn record_moderation(
+    agent: &mut AgentAccount,
+    reason: u16,
+    evidence_hash: [u8; 32],
+) -> Result<()> {
+    agent.moderation_reason = reason;
+    agent.moderation_evidence_hash = evidence_hash;
+    agent.moderated_at = Clock::get()?.unix_timestamp;
+    Ok(())
+}
 
-#[event]
-pub struct DecisionLogged {
-    pub agent: Pubkey,
-    pub merkle_root: [u8; 32],
-    pub timestamp: i64,
+/// Lamports an organisation holds above its rent-exempt minimum.
+fn organization_budget(organization: &AccountInfo) -> Result<u64> {
+    let rent = Rent::get()?.minimum_balance(organization.data_len());
+    Ok(organization.lamports().saturating_sub(rent))
 }
 
-#[event]
-pub struct OutcomeAttested {
-    pub agent: Pubkey,
-    pub memory_log: Pubkey,
-    pub success: bool,
-    pub new_reputation: u64,
+/// Drop `agent` from `organization`: take its last reported stats back out
+/// of the totals and reset its membership and spend.
+fn detach_from_organization(
+    organization: &mut Account<Organization>,
+    agent: &mut Account<AgentAccount>,
+) -> Result<()> {
+    organization.agent_count = organization.agent_count.saturating_sub(1);
+    organization.total_reputation = organization
+        .total_reputation
+        .checked_sub(agent.org_reported_reputation)
+        .ok_or(ErrorCode::ReputationOverflow)?;
+    organization.total_logs = organization
+        .total_logs
+        .saturating_sub(agent.org_reported_logs);
+    agent.organization = None;
+    agent.org_reported_reputation = 0;
+    agent.org_reported_logs = 0;
+    agent.org_spent = 0;
+
+    emit!(OrganizationMemberRemoved {
+        organization: organization.key(),
+        agent: agent.key(),
+        agent_count: organization.agent_count,
+    });
+
+    msg!(
+        "Agent {} removed from organization {}",
+        agent.agent_id,
+        organization.org_id
+    );
+    Ok(())
 }
 
-#[event]
-pub struct ModuleRegistered {
-    pub module: Pubkey,
-    pub module_id: String,
-    pub creator: Pubkey,
-    pub price_lamports: u64,
-    pub ipfs_hash: String,
+/// Where a module purchase is paid from.
+enum PurchasePayer<'info> {
+    Buyer {
+        buyer: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+    },
+    Organization(AccountInfo<'info>),
 }
 
-#[event]
-pub struct ModulePurchased {
-    pub module: Pubkey,
-    pub agent: Pubkey,
-    pub price_paid: u64,
-    pub platform_fee: u64,
-    pub creator_royalty: u64,
-    pub referral_fee: u64,
+impl<'info> PurchasePayer<'info> {
+    /// Pay `amount` to `recipient`, which must be the account at `expected`.
+    fn pay(
+        &self,
+        recipient: &AccountInfo<'info>,
+        expected: &Pubkey,
+        amount: u64,
+    ) -> Result<()> {
+        match self {
+            PurchasePayer::Buyer {
+                buyer,
+                system_program,
+            } => {
+                invoke(
+                    &system_instruction::transfer(buyer.key, expected, amount),
+                    &[buyer.clone(), recipient.clone(), system_program.clone()],
+                )?;
+                Ok(())
+            }
+            PurchasePayer::Organization(organization) => {
+                require_keys_eq!(
+                    *recipient.key,
+                    *expected,
+                    ErrorCode::InvalidPaymentRecipient
+                );
+                move_lamports(organization, recipient, amount)
+            }
+        }
+    }
+}
+
+/// Move lamports out of a program-owned account.
+fn move_lamports(
+    from: &AccountInfo,
+    to: &AccountInfo,
+    amount: u64,
+) -> Result<()> {
+    **from.try_borrow_mut_lamports()? = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(ErrorCode::InvalidBondAmount)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(ErrorCode::InvalidBondAmount)?;
+    Ok(())
+}
+
+/// Grow `account` to `new_len`, topping its lamports up to the new rent-exempt
+/// minimum from `payer` first.
+fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    new_len: usize,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(new_len);
+    let top_up = rent.saturating_sub(account.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, top_up),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    account.realloc(new_len, false)?;
+    Ok(())
 }
 
 // ============================================================================
@@ -634,4 +6868,186 @@ pub enum ErrorCode {
     FeeTooHigh,
     #[msg("Module not active")]
     ModuleNotActive,
+    #[msg("Account is not a legacy timestamp-seeded memory log")]
+    NotLegacyMemoryLog,
+    #[msg("All legacy memory logs have already been migrated")]
+    LegacyLogsMigrated,
+    #[msg("Legacy memory logs must be migrated oldest first")]
+    LegacyLogOutOfOrder,
+    #[msg("Legacy memory log sequence does not match the migration cursor")]
+    LegacyLogSequenceMismatch,
+    #[msg("Decision batch is empty")]
+    EmptyBatch,
+    #[msg("Decision batch too large (max 16 leaves)")]
+    BatchTooLarge,
+    #[msg("Provide exactly one memory log or decision batch")]
+    InvalidRootAccount,
+    #[msg("Merkle inclusion proof does not match the logged root")]
+    InvalidInclusionProof,
+    #[msg("Decision logs do not form an unbroken chain")]
+    BrokenLogChain,
+    #[msg("Account is not a legacy agent account")]
+    NotLegacyAgent,
+    #[msg("Memory log was not logged as a commitment")]
+    NotACommitment,
+    #[msg("Decision already revealed")]
+    AlreadyRevealed,
+    #[msg("Revealed data does not match the stored commitment")]
+    RevealMismatch,
+    #[msg("Invalid reveal window")]
+    InvalidRevealWindow,
+    #[msg("Reveal window has not opened yet")]
+    RevealTooEarly,
+    #[msg("Reveal deadline has passed")]
+    RevealWindowClosed,
+    #[msg("Decision has not missed its reveal deadline")]
+    RevealNotMissed,
+    #[msg("Attester is not registered or has been suspended")]
+    AttesterNotActive,
+    #[msg("Agents cannot attest their own outcomes")]
+    SelfAttestation,
+    #[msg("Attester is not on this agent's allowlist")]
+    AttesterNotAllowed,
+    #[msg("Too many allowed attesters (max 10)")]
+    TooManyAllowedAttesters,
+    #[msg("Attester bond below the minimum (0.1 SOL)")]
+    InsufficientAttesterBond,
+    #[msg("Invalid bond amount")]
+    InvalidBondAmount,
+    #[msg("Bond is locked by open challenges or a recent attestation")]
+    BondLocked,
+    #[msg("Challenge window has closed")]
+    ChallengeWindowClosed,
+    #[msg("Attestation has been rejected")]
+    AttestationRejected,
+    #[msg("Attestation is not pending")]
+    AttestationNotPending,
+    #[msg("Attestation is not under dispute")]
+    AttestationNotDisputed,
+    #[msg("Dispute window is still open")]
+    DisputeWindowOpen,
+    #[msg("Dispute window has closed")]
+    DisputeWindowClosed,
+    #[msg("Invalid dispute window (max 30 days)")]
+    InvalidDisputeWindow,
+    #[msg("Account is not a legacy platform config")]
+    NotLegacyPlatformConfig,
+    #[msg("Invalid round quorum (2 to 9 attesters)")]
+    InvalidQuorum,
+    #[msg("Attestation round already finalized")]
+    RoundFinalized,
+    #[msg("Attester already voted in this round")]
+    DuplicateVote,
+    #[msg("Only the platform authority or an attester may open a round")]
+    UnauthorizedRoundOpener,
+    #[msg("Round has already reached quorum")]
+    QuorumReached,
+    #[msg("Round has not reached quorum")]
+    QuorumNotReached,
+    #[msg("Only a voter from the majority may settle the round")]
+    NotMajorityVoter,
+    #[msg("Attestation round has expired")]
+    RoundExpired,
+    #[msg("Round has not expired or already reached quorum")]
+    RoundNotExpired,
+    #[msg("Reputation half-life cannot be negative")]
+    InvalidHalfLife,
+    #[msg("Invalid domain (max 32 chars of a-z, 0-9, - and _)")]
+    InvalidDomain,
+    #[msg("Domain reputation account required for a domain-tagged log")]
+    MissingDomainReputation,
+    #[msg("Invalid scoring model parameters")]
+    InvalidScoringModel,
+    #[msg("Agent is not controlled by or linked to the attester")]
+    AttesterAgentMismatch,
+    #[msg("Attester is already linked to an agent")]
+    AttesterAgentAlreadyLinked,
+    #[msg("Elo scoring requires the attester's linked agent")]
+    MissingAttesterAgent,
+    #[msg("Reputation history account required for this agent")]
+    MissingReputationHistory,
+    #[msg("No reputation checkpoint at or before the requested time")]
+    ReputationCheckpointNotFound,
+    #[msg("Agent reputation is below the required minimum")]
+    ReputationTooLow,
+    #[msg("Agent has too few finalised outcomes")]
+    TooFewOutcomes,
+    #[msg("Agent success rate is below the required minimum")]
+    SuccessRateTooLow,
+    #[msg("Agent account is younger than the required minimum age")]
+    AgentTooNew,
+    #[msg("Agents cannot endorse themselves")]
+    SelfEndorsement,
+    #[msg("Endorser needs positive reputation to endorse")]
+    EndorserReputationTooLow,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Signer is not the agent's recovery authority")]
+    NotRecoveryAuthority,
+    #[msg("Delegate was granted under a previous authority")]
+    StaleDelegate,
+    #[msg("Delegate grant needs a future expiry and a positive log limit")]
+    InvalidDelegateGrant,
+    #[msg("Delegate grant has expired")]
+    DelegateExpired,
+    #[msg("Delegate grant has used all of its logs")]
+    DelegateLogLimitReached,
+    #[msg("Delegate grant does not allow module purchases")]
+    DelegateCannotPurchase,
+    #[msg("Signer is neither the agent authority nor its delegate")]
+    UnauthorizedSigner,
+    #[msg("Profile field too long")]
+    ProfileFieldTooLong,
+    #[msg("Agent is retired")]
+    AgentRetired,
+    #[msg("Agent is not retired")]
+    AgentNotRetired,
+    #[msg("Retention period has not passed")]
+    RetentionPeriodActive,
+    #[msg("Close the log's attestation first")]
+    AttestationStillOpen,
+    #[msg("Close the log's attestation round first")]
+    AttestationRoundStillOpen,
+    #[msg("Attestation is still pending or disputed")]
+    AttestationUnsettled,
+    #[msg("Attestation has an open challenge")]
+    ChallengeStillOpen,
+    #[msg("Attestation has an open dispute")]
+    DisputeStillOpen,
+    #[msg("Agent ID must be lowercase letters, digits, '-' or '_'")]
+    InvalidAgentId,
+    #[msg("Agent name is reserved")]
+    NameUnavailable,
+    #[msg("Too many reserved names")]
+    TooManyReservedNames,
+    #[msg("Name reservation has not expired")]
+    NameReservationActive,
+    #[msg("Signer is not an admin of this organization")]
+    NotOrganizationAdmin,
+    #[msg("Agent is not a member of this organization")]
+    NotOrganizationMember,
+    #[msg("Organization must have between 1 and 8 admins")]
+    InvalidOrganizationAdmins,
+    #[msg("Invalid or insufficient organization budget amount")]
+    InvalidBudgetAmount,
+    #[msg("Purchase exceeds the organization's per-member spend limit")]
+    MemberSpendLimitExceeded,
+    #[msg("Payment recipient does not match the expected account")]
+    InvalidPaymentRecipient,
+    #[msg("Agent is frozen by the platform")]
+    AgentFrozen,
+    #[msg("Agent is not frozen")]
+    AgentNotFrozen,
+    #[msg("An appeal is already pending")]
+    AppealPending,
+    #[msg("No appeal is pending")]
+    NoAppealPending,
+    #[msg("Agent versions must be activated in sequence")]
+    InvalidAgentVersion,
+    #[msg("Attestation is not finalized")]
+    AttestationNotFinalized,
+    #[msg("Challenge window is still open")]
+    ChallengeWindowOpen,
+    #[msg("Outcome already tallied for its version")]
+    VersionAlreadyTallied,
 }
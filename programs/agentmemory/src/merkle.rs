@@ -0,0 +1,200 @@
+use anchor_lang::solana_program::keccak;
+
+/// Binary Merkle tree over decision leaves.
+///
+/// A leaf is `keccak(input_hash || logic_hash)`, which is exactly the
+/// `merkle_root` a single `log_decision` stores, so a one-decision log is a
+/// one-leaf tree. Internal nodes are `keccak(0x01 || left || right)`; the
+/// prefix keeps a 64-byte leaf preimage from ever being read as a node.
+/// When a level has an odd number of nodes the last one is promoted to the
+/// next level unchanged rather than paired with a copy of itself.
+pub const NODE_PREFIX: &[u8] = &[1];
+
+/// Hash a decision leaf from its input and logic hashes.
+pub fn hash_leaf(input_hash: &[u8; 32], logic_hash: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[input_hash, logic_hash]).to_bytes()
+}
+
+/// Hash two sibling nodes into their parent.
+pub fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[NODE_PREFIX, left, right]).to_bytes()
+}
+
+/// Compute the root of the tree built over `leaves`. Returns `None` for an
+/// empty slice.
+pub fn compute_root(leaves: &[[u8; 32]]) -> Option<[u8; 32]> {
+    if leaves.is_empty() {
+        return None;
+    }
+
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_node(left, right),
+                [last] => *last,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    Some(level[0])
+}
+
+/// Check that `leaf` sits at `index` in a tree of `leaf_count` leaves whose
+/// root is `root`. `proof` lists the sibling hashes from the leaf upwards,
+/// skipping levels where the node was promoted without a sibling. Every
+/// sibling must be consumed for the proof to be accepted.
+pub fn verify_proof(
+    leaf: &[u8; 32],
+    index: u32,
+    leaf_count: u32,
+    proof: &[[u8; 32]],
+    root: &[u8; 32],
+) -> bool {
+    if index >= leaf_count {
+        return false;
+    }
+
+    let mut node = *leaf;
+    let mut index = index;
+    let mut width = leaf_count;
+    let mut siblings = proof.iter();
+
+    while width > 1 {
+        if index % 2 == 1 {
+            match siblings.next() {
+                Some(sibling) => node = hash_node(sibling, &node),
+                None => return false,
+            }
+        } else if index + 1 < width {
+            match siblings.next() {
+                Some(sibling) => node = hash_node(&node, sibling),
+                None => return false,
+            }
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+
+    siblings.next().is_none() && node == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: u8) -> Vec<[u8; 32]> {
+        (0..count)
+            .map(|i| hash_leaf(&[i; 32], &[i + 100; 32]))
+            .collect()
+    }
+
+    /// Sibling path for `index`, built level by level the same way
+    /// `compute_root` pairs nodes.
+    fn proof_for(leaves: &[[u8; 32]], index: usize) -> Vec<[u8; 32]> {
+        let mut proof = Vec::new();
+        let mut level = leaves.to_vec();
+        let mut index = index;
+        while level.len() > 1 {
+            let sibling = index ^ 1;
+            if sibling < level.len() {
+                proof.push(level[sibling]);
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_node(left, right),
+                    [last] => *last,
+                    _ => unreachable!(),
+                })
+                .collect();
+            index /= 2;
+        }
+        proof
+    }
+
+    #[test]
+    fn empty_tree_has_no_root() {
+        assert_eq!(compute_root(&[]), None);
+    }
+
+    #[test]
+    fn single_leaf_is_its_own_root() {
+        let leaves = leaves(1);
+        assert_eq!(compute_root(&leaves), Some(leaves[0]));
+        assert!(verify_proof(&leaves[0], 0, 1, &[], &leaves[0]));
+    }
+
+    #[test]
+    fn pairs_leaves_with_prefixed_nodes() {
+        let leaves = leaves(4);
+        let left = hash_node(&leaves[0], &leaves[1]);
+        let right = hash_node(&leaves[2], &leaves[3]);
+        assert_eq!(compute_root(&leaves), Some(hash_node(&left, &right)));
+        assert_ne!(
+            hash_node(&leaves[0], &leaves[1]),
+            keccak::hashv(&[&leaves[0], &leaves[1]]).to_bytes()
+        );
+    }
+
+    #[test]
+    fn promotes_the_odd_node() {
+        let leaves = leaves(3);
+        let expected =
+            hash_node(&hash_node(&leaves[0], &leaves[1]), &leaves[2]);
+        assert_eq!(compute_root(&leaves), Some(expected));
+
+        // The promoted leaf needs only the sibling from the level above.
+        assert_eq!(proof_for(&leaves, 2).len(), 1);
+    }
+
+    #[test]
+    fn verifies_every_leaf_for_odd_and_even_counts() {
+        for count in 1..=16 {
+            let leaves = leaves(count);
+            let root = compute_root(&leaves).unwrap();
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = proof_for(&leaves, index);
+                assert!(
+                    verify_proof(
+                        leaf,
+                        index as u32,
+                        count as u32,
+                        &proof,
+                        &root
+                    ),
+                    "leaf {index} of {count}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_tampered_proofs() {
+        let leaves = leaves(5);
+        let root = compute_root(&leaves).unwrap();
+        let proof = proof_for(&leaves, 1);
+        assert!(verify_proof(&leaves[1], 1, 5, &proof, &root));
+
+        // Wrong leaf, position or tree size; the size only matters where a
+        // node was promoted.
+        assert!(!verify_proof(&leaves[2], 1, 5, &proof, &root));
+        assert!(!verify_proof(&leaves[1], 0, 5, &proof, &root));
+        let last = proof_for(&leaves, 4);
+        assert!(verify_proof(&leaves[4], 4, 5, &last, &root));
+        assert!(!verify_proof(&leaves[4], 4, 6, &last, &root));
+        assert!(!verify_proof(&leaves[1], 5, 5, &proof, &root));
+
+        // A flipped sibling byte.
+        let mut tampered = proof.clone();
+        tampered[0][0] ^= 1;
+        assert!(!verify_proof(&leaves[1], 1, 5, &tampered, &root));
+
+        // Too few or too many siblings.
+        assert!(!verify_proof(&leaves[1], 1, 5, &proof[..2], &root));
+        let mut extended = proof.clone();
+        extended.push(root);
+        assert!(!verify_proof(&leaves[1], 1, 5, &extended, &root));
+    }
+}
@@ -0,0 +1,237 @@
+/// Fixed-point precision used by the decay approximation.
+const PRECISION: i128 = 1_000_000_000;
+
+/// `ln(2)^k / k!` scaled by `PRECISION`, for k = 1..=4.
+const DECAY_TERMS: [i128; 4] =
+    [693_147_181, 240_226_507, 55_504_109, 9_618_129];
+
+/// Decay `value` by `2^(-elapsed / half_life)` without floating point.
+///
+/// Whole half-lives are applied as right shifts; the remaining fraction uses
+/// a fourth-order series for `2^-f`, which stays within 0.25% of the exact
+/// factor. A non-positive `half_life` disables decay.
+pub fn decay(value: u64, elapsed: i64, half_life: i64) -> u64 {
+    if half_life <= 0 || elapsed <= 0 || value == 0 {
+        return value;
+    }
+
+    let halvings = elapsed / half_life;
+    if halvings >= 64 {
+        return 0;
+    }
+    let value = value >> halvings;
+
+    let fraction =
+        (elapsed % half_life) as i128 * PRECISION / half_life as i128;
+    let mut factor = PRECISION;
+    let mut power = PRECISION;
+    for (k, term) in DECAY_TERMS.iter().enumerate() {
+        power = power * fraction / PRECISION;
+        let contribution = term * power / PRECISION;
+        if k % 2 == 0 {
+            factor -= contribution;
+        } else {
+            factor += contribution;
+        }
+    }
+
+    (value as i128 * factor / PRECISION) as u64
+}
+
+/// Signed variant of [`decay`]: the magnitude decays towards zero and the
+/// sign is kept, so negative reputation recovers at the same rate positive
+/// reputation fades.
+pub fn decay_signed(value: i64, elapsed: i64, half_life: i64) -> i64 {
+    let magnitude = decay(value.unsigned_abs(), elapsed, half_life);
+    let magnitude = i64::try_from(magnitude).unwrap_or(i64::MAX);
+    if value < 0 {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Elo ratings differ by 400 points per factor of ten in odds. Expressed as
+/// a half-life for [`decay`] in hundredths of a point: `400 / log2(10)`.
+const ELO_HALF_LIFE: i64 = 12_041;
+
+/// Rating gaps beyond this are treated as this wide; the expected score is
+/// already within 1e-10 of 0 or 1.
+const ELO_MAX_GAP: i64 = 4_000;
+
+/// Expected score of `rating` against `opponent`, scaled by `PRECISION`:
+/// `1 / (1 + 10^((opponent - rating) / 400))`.
+fn elo_expected(rating: i64, opponent: i64) -> i128 {
+    let gap = rating
+        .saturating_sub(opponent)
+        .clamp(-ELO_MAX_GAP, ELO_MAX_GAP);
+    let odds = decay(PRECISION as u64, gap.abs() * 100, ELO_HALF_LIFE) as i128;
+    if gap >= 0 {
+        PRECISION * PRECISION / (PRECISION + odds)
+    } else {
+        odds * PRECISION / (odds + PRECISION)
+    }
+}
+
+/// Elo update for an outcome judged against an `opponent` rating: a success
+/// the ratings did not expect moves the score by up to `k_factor`.
+pub fn elo_delta(
+    rating: i64,
+    opponent: i64,
+    k_factor: u32,
+    success: bool,
+) -> i64 {
+    let actual = if success { PRECISION } else { 0 };
+    let expected = elo_expected(rating, opponent);
+    (k_factor as i128 * (actual - expected) / PRECISION) as i64
+}
+
+/// Scale of a Beta-Bernoulli estimate: 10_000 is certain success.
+pub const BETA_SCALE: u128 = 10_000;
+
+/// Posterior mean success rate under a `Beta(prior_alpha, prior_beta)`
+/// prior after `successes` and `failures`, scaled by `BETA_SCALE`.
+pub fn beta_estimate(
+    successes: u64,
+    failures: u64,
+    prior_alpha: u32,
+    prior_beta: u32,
+) -> i64 {
+    let alpha = prior_alpha as u128 + successes as u128;
+    let total = alpha + prior_beta as u128 + failures as u128;
+    if total == 0 {
+        return 0;
+    }
+    (alpha * BETA_SCALE / total) as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[test]
+    fn decay_is_a_no_op_without_elapsed_time_or_half_life() {
+        assert_eq!(decay(1_000, 0, DAY), 1_000);
+        assert_eq!(decay(1_000, -DAY, DAY), 1_000);
+        assert_eq!(decay(1_000, DAY, 0), 1_000);
+        assert_eq!(decay(1_000, DAY, -DAY), 1_000);
+        assert_eq!(decay(0, DAY, DAY), 0);
+    }
+
+    #[test]
+    fn decay_halves_exactly_on_whole_half_lives() {
+        assert_eq!(decay(1_000, DAY, DAY), 500);
+        assert_eq!(decay(1_000, 2 * DAY, DAY), 250);
+        assert_eq!(decay(u64::MAX, 63 * DAY, DAY), 1);
+        assert_eq!(decay(u64::MAX, 64 * DAY, DAY), 0);
+        assert_eq!(decay(u64::MAX, i64::MAX, DAY), 0);
+    }
+
+    #[test]
+    fn decay_fraction_stays_within_tolerance() {
+        // 2^-0.5 and 2^-0.25, with the documented 0.25% bound.
+        for (elapsed, exact) in [
+            (DAY / 2, std::f64::consts::FRAC_1_SQRT_2),
+            (DAY / 4, 0.840_896_42),
+        ] {
+            let decayed = decay(1_000_000_000, elapsed, DAY) as f64 / 1e9;
+            assert!((decayed - exact).abs() / exact < 0.0025, "{decayed}");
+        }
+        // The largest value does not overflow the fixed-point product.
+        let decayed = decay(u64::MAX, DAY / 2, DAY);
+        assert!(decayed < u64::MAX && decayed > u64::MAX / 2);
+    }
+
+    #[test]
+    fn decay_never_increases_with_time() {
+        let mut previous = u64::MAX;
+        for elapsed in (0..=3 * DAY).step_by(3_600) {
+            let decayed = decay(1_000_000, elapsed, DAY);
+            assert!(decayed <= previous, "at {elapsed}s");
+            previous = decayed;
+        }
+    }
+
+    #[test]
+    fn decay_signed_keeps_the_sign() {
+        assert_eq!(decay_signed(1_000, DAY, DAY), 500);
+        assert_eq!(decay_signed(-1_000, DAY, DAY), -500);
+        assert_eq!(decay_signed(-1_000, 2 * DAY, DAY), -250);
+        assert_eq!(
+            decay_signed(-1_000, DAY / 2, DAY),
+            -decay_signed(1_000, DAY / 2, DAY)
+        );
+        assert_eq!(decay_signed(0, DAY, DAY), 0);
+    }
+
+    #[test]
+    fn decay_signed_saturates_at_the_extremes() {
+        assert_eq!(decay_signed(i64::MAX, 0, DAY), i64::MAX);
+        assert_eq!(decay_signed(i64::MIN, 0, DAY), -i64::MAX);
+        assert_eq!(decay_signed(i64::MIN, 64 * DAY, DAY), 0);
+    }
+
+    #[test]
+    fn elo_even_match_moves_half_the_k_factor() {
+        assert_eq!(elo_delta(1_000, 1_000, 32, true), 16);
+        assert_eq!(elo_delta(1_000, 1_000, 32, false), -16);
+        assert_eq!(elo_delta(-500, -500, 32, true), 16);
+    }
+
+    #[test]
+    fn elo_is_symmetric_between_the_two_sides() {
+        for (rating, opponent) in [(1_200, 1_000), (0, 400), (-300, 900)] {
+            for k_factor in [16, 32, 1_000] {
+                let win = elo_delta(rating, opponent, k_factor, true);
+                let loss = elo_delta(opponent, rating, k_factor, false);
+                assert!((win + loss).abs() <= 1, "{rating} v {opponent}");
+            }
+        }
+    }
+
+    #[test]
+    fn elo_rewards_upsets_more_than_expected_wins() {
+        // A 400-point gap is 10:1 odds: an expected score of 10/11.
+        assert_eq!(elo_delta(1_400, 1_000, 100, true), 9);
+        assert_eq!(elo_delta(1_000, 1_400, 100, true), 90);
+        assert!(
+            elo_delta(1_000, 1_400, 32, true)
+                > elo_delta(1_400, 1_000, 32, true)
+        );
+    }
+
+    #[test]
+    fn elo_clamps_extreme_gaps() {
+        assert_eq!(elo_delta(i64::MAX, i64::MIN, 32, true), 0);
+        assert_eq!(elo_delta(i64::MAX, i64::MIN, 32, false), -32);
+        assert_eq!(elo_delta(i64::MIN, i64::MAX, 32, true), 32);
+        assert_eq!(
+            elo_delta(0, 4_000, 32, true),
+            elo_delta(0, 40_000, 32, true)
+        );
+    }
+
+    #[test]
+    fn beta_estimate_matches_known_posteriors() {
+        // Uniform prior with no evidence: a coin flip.
+        assert_eq!(beta_estimate(0, 0, 1, 1), 5_000);
+        // Laplace's rule of succession: (s + 1) / (n + 2).
+        assert_eq!(beta_estimate(3, 1, 1, 1), 6_666);
+        assert_eq!(beta_estimate(9, 0, 1, 1), 9_090);
+        assert_eq!(beta_estimate(0, 9, 1, 1), 909);
+        // A stronger prior moves less on the same evidence.
+        assert_eq!(beta_estimate(3, 1, 10, 10), 5_416);
+        assert_eq!(beta_estimate(0, 0, 0, 0), 0);
+    }
+
+    #[test]
+    fn beta_estimate_does_not_overflow() {
+        assert_eq!(beta_estimate(u64::MAX, 0, u32::MAX, 1), 9_999);
+        assert_eq!(
+            beta_estimate(u64::MAX, u64::MAX, u32::MAX, u32::MAX),
+            5_000
+        );
+    }
+}
@@ -6,6 +6,11 @@ export interface AgentAccount {
   agentId: string;
   authority: PublicKey;
   reputation: number;
+  totalPositive: number;
+  totalNegative: number;
+  successCount: number;
+  failureCount: number;
+  streak: number;
   totalLogs: number;
   totalAttestations: number;
   bump: number;
@@ -13,6 +18,7 @@ export interface AgentAccount {
 
 export interface MemoryLogAccount {
   agent: PublicKey;
+  sequence: number;
   inputHash: number[];
   logicHash: number[];
   merkleRoot: number[];
@@ -23,6 +29,7 @@ export interface MemoryLogAccount {
 
 export interface AttestationAccount {
   memoryLog: PublicKey;
+  attester: PublicKey;
   outcomeHash: number[];
   success: boolean;
   scoreDelta: number;
@@ -93,7 +100,40 @@ export class AgentMemoryClient {
     );
   }
 
-  getMemoryLogPDA(agentPubkey: PublicKey, timestamp: number): [PublicKey, number] {
+  getNameReservationPDA(name: string): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("name_reservation"), Buffer.from(name)],
+      this.program.programId
+    );
+  }
+
+  getOrganizationPDA(orgId: string): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("organization"), Buffer.from(orgId)],
+      this.program.programId
+    );
+  }
+
+  getAgentVersionPDA(agentPubkey: PublicKey, version: number): [PublicKey, number] {
+    const buf = Buffer.alloc(4);
+    buf.writeUInt32LE(version);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("agent_version"), agentPubkey.toBuffer(), buf],
+      this.program.programId
+    );
+  }
+
+  getMemoryLogPDA(agentPubkey: PublicKey, sequence: number): [PublicKey, number] {
+    const buf = Buffer.alloc(8);
+    buf.writeBigUInt64LE(BigInt(sequence));
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("memory"), agentPubkey.toBuffer(), buf],
+      this.program.programId
+    );
+  }
+
+  // Logs created before sequence addressing were seeded with their timestamp.
+  getLegacyMemoryLogPDA(agentPubkey: PublicKey, timestamp: number): [PublicKey, number] {
     const buf = Buffer.alloc(8);
     buf.writeBigInt64LE(BigInt(timestamp));
     return PublicKey.findProgramAddressSync(
@@ -102,6 +142,30 @@ export class AgentMemoryClient {
     );
   }
 
+  /**
+   * Address of the agent's log with `sequence`. Logs created under the
+   * legacy timestamp seeds (sequences below the agent's `legacyLogs`) keep
+   * that address after migration, so they are found by a `getProgramAccounts`
+   * filter on the `agent` (offset 8) and `sequence` (offset 40) fields.
+   * Returns null for a legacy log that has not been migrated yet.
+   */
+  async findMemoryLog(agentPubkey: PublicKey, sequence: number): Promise<PublicKey | null> {
+    const agent = await this.program.account.agentAccount.fetch(agentPubkey);
+    if (sequence >= (agent.legacyLogs as BN).toNumber()) {
+      return this.getMemoryLogPDA(agentPubkey, sequence)[0];
+    }
+
+    const buf = Buffer.alloc(8);
+    buf.writeBigUInt64LE(BigInt(sequence));
+    const matches = await this.program.account.memoryLog.all([
+      // Unmigrated legacy logs share the discriminator but not the size.
+      { dataSize: this.program.account.memoryLog.size },
+      { memcmp: { offset: 8, bytes: agentPubkey.toBase58() } },
+      { memcmp: { offset: 40, bytes: anchor.utils.bytes.bs58.encode(buf) } },
+    ]);
+    return matches.length > 0 ? matches[0].publicKey : null;
+  }
+
   getAttestationPDA(memoryLogPubkey: PublicKey): [PublicKey, number] {
     return PublicKey.findProgramAddressSync(
       [Buffer.from("attest"), memoryLogPubkey.toBuffer()],
@@ -109,6 +173,50 @@ export class AgentMemoryClient {
     );
   }
 
+  getAttesterPDA(attester: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("attester"), attester.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  getAttesterAllowlistPDA(agentPubkey: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("attester_allowlist"), agentPubkey.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  getDomainReputationPDA(agentPubkey: PublicKey, domain: string): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("domain_rep"), agentPubkey.toBuffer(), Buffer.from(domain)],
+      this.program.programId
+    );
+  }
+
+  getDelegatePDA(agentPubkey: PublicKey, sessionKey: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("delegate"), agentPubkey.toBuffer(), sessionKey.toBuffer()],
+      this.program.programId
+    );
+  }
+
+  /** Delegate grant to pass when the wallet is a session key, not the authority. */
+  private async delegateFor(agentPda: PublicKey): Promise<PublicKey | null> {
+    const agent = await this.program.account.agentAccount.fetch(agentPda);
+    const wallet = this.provider.wallet.publicKey;
+    return (agent.authority as PublicKey).equals(wallet)
+      ? null
+      : this.getDelegatePDA(agentPda, wallet)[0];
+  }
+
+  getReputationHistoryPDA(agentPubkey: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("reputation_history"), agentPubkey.toBuffer()],
+      this.program.programId
+    );
+  }
+
   getModulePDA(moduleId: string): [PublicKey, number] {
     return PublicKey.findProgramAddressSync(
       [Buffer.from("module"), Buffer.from(moduleId)],
@@ -127,42 +235,88 @@ export class AgentMemoryClient {
   // Core Instructions: Agent + Memory
   // ============================================================================
 
-  async initializeAgent(agentId: string): Promise<string> {
+  /** Register an agent, optionally under an organisation the wallet administers. */
+  async initializeAgent(agentId: string, orgId?: string): Promise<string> {
     const [agentPda] = this.getAgentPDA(agentId);
 
     const tx = await this.program.methods
       .initializeAgent(agentId)
       .accounts({
         agent: agentPda,
+        platformConfig: this.getPlatformConfigPDA()[0],
+        nameReservation: this.getNameReservationPDA(agentId)[0],
         authority: this.provider.wallet.publicKey,
         systemProgram: SystemProgram.programId,
+        organization: orgId ? this.getOrganizationPDA(orgId)[0] : null,
       })
       .rpc();
 
     return tx;
   }
 
+  async reserveAgentName(name: string): Promise<string> {
+    const [configPda] = this.getPlatformConfigPDA();
+    const config = await this.program.account.platformConfig.fetch(configPda);
+
+    const tx = await this.program.methods
+      .reserveAgentName(name)
+      .accounts({
+        nameReservation: this.getNameReservationPDA(name)[0],
+        agent: this.getAgentPDA(name)[0],
+        platformConfig: configPda,
+        treasury: config.treasury,
+        owner: this.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  /**
+   * Migrate the legacy log created at `timestamp`. `sequence` is its rank by
+   * timestamp among the agent's legacy logs; the program rejects it unless
+   * it is the next one due, so logs must be migrated oldest first.
+   */
+  async migrateMemoryLog(
+    agentId: string,
+    timestamp: number,
+    sequence: number
+  ): Promise<string> {
+    const [agentPda] = this.getAgentPDA(agentId);
+    const [legacyPda] = this.getLegacyMemoryLogPDA(agentPda, timestamp);
+
+    return await this.program.methods
+      .migrateMemoryLog(new BN(sequence))
+      .accounts({
+        agent: agentPda,
+        legacyMemoryLog: legacyPda,
+        authority: this.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  }
+
   async logDecision(
     agentId: string,
     inputData: string,
-    logicData: string
-  ): Promise<{ tx: string; memoryLogPubkey: PublicKey; timestamp: number }> {
+    logicData: string,
+    domain: string = ""
+  ): Promise<{ tx: string; memoryLogPubkey: PublicKey; sequence: number }> {
     const [agentPda] = this.getAgentPDA(agentId);
 
-    const clock = await this.provider.connection.getSlot();
-    const blockTime = await this.provider.connection.getBlockTime(clock);
-    if (!blockTime) throw new Error("Could not get block time");
-
-    const ts = blockTime + 1;
-    const [memoryLogPda] = this.getMemoryLogPDA(agentPda, ts);
+    const agent = await this.program.account.agentAccount.fetch(agentPda);
+    const sequence = (agent.totalLogs as BN).toNumber();
+    const [memoryLogPda] = this.getMemoryLogPDA(agentPda, sequence);
 
     const tx = await this.program.methods
-      .logDecision(inputData, logicData)
+      .logDecision(inputData, logicData, domain)
       .accounts({
         agent: agentPda,
         memoryLog: memoryLogPda,
         authority: this.provider.wallet.publicKey,
         systemProgram: SystemProgram.programId,
+        delegate: await this.delegateFor(agentPda),
       })
       .rpc({ skipPreflight: true });
 
@@ -171,7 +325,7 @@ export class AgentMemoryClient {
       throw new Error(`Transaction failed: ${JSON.stringify(confirmation.value.err)}`);
     }
 
-    return { tx, memoryLogPubkey: memoryLogPda, timestamp: ts };
+    return { tx, memoryLogPubkey: memoryLogPda, sequence };
   }
 
   async attestOutcome(
@@ -183,6 +337,16 @@ export class AgentMemoryClient {
   ): Promise<string> {
     const [agentPda] = this.getAgentPDA(agentId);
     const [attestationPda] = this.getAttestationPDA(memoryLogPubkey);
+    const memoryLog = await this.program.account.memoryLog.fetch(memoryLogPubkey);
+    const domainReputation = memoryLog.domain
+      ? this.getDomainReputationPDA(agentPda, memoryLog.domain as string)[0]
+      : null;
+    const agent = await this.program.account.agentAccount.fetch(agentPda);
+    const reputationHistory = agent.hasReputationHistory
+      ? this.getReputationHistoryPDA(agentPda)[0]
+      : null;
+    const [attesterPda] = this.getAttesterPDA(this.provider.wallet.publicKey);
+    const attester = await this.program.account.attesterAccount.fetch(attesterPda);
 
     const tx = await this.program.methods
       .attestOutcome(outcomeData, success, new BN(scoreDelta))
@@ -190,14 +354,37 @@ export class AgentMemoryClient {
         agent: agentPda,
         memoryLog: memoryLogPubkey,
         attestation: attestationPda,
-        authority: this.provider.wallet.publicKey,
+        attesterAccount: attesterPda,
+        attesterAllowlist: this.getAttesterAllowlistPDA(agentPda)[0],
+        platformConfig: this.getPlatformConfigPDA()[0],
+        attester: this.provider.wallet.publicKey,
         systemProgram: SystemProgram.programId,
+        domainReputation,
+        reputationHistory,
+        attesterAgent: (attester.agent as PublicKey | null) ?? null,
       })
       .rpc();
 
     return tx;
   }
 
+  /**
+   * Permanently link the wallet's attester registration to one of its
+   * agents, whose reputation Elo scoring rates attested agents against.
+   */
+  async linkAttesterAgent(agentId: string): Promise<string> {
+    const [agentPda] = this.getAgentPDA(agentId);
+
+    return await this.program.methods
+      .linkAttesterAgent()
+      .accounts({
+        attesterAccount: this.getAttesterPDA(this.provider.wallet.publicKey)[0],
+        agent: agentPda,
+        attester: this.provider.wallet.publicKey,
+      })
+      .rpc();
+  }
+
   // ============================================================================
   // Marketplace Instructions: Platform + Modules + Purchases
   // ============================================================================
@@ -253,6 +440,7 @@ export class AgentMemoryClient {
 
     const moduleAccount = await this.getModule(moduleId);
     const config = await this.getPlatformConfig();
+    const agent = await this.program.account.agentAccount.fetch(agentPda);
 
     const accounts: any = {
       moduleMetadata: modulePda,
@@ -264,6 +452,9 @@ export class AgentMemoryClient {
       creatorWallet: moduleAccount.creator,
       referrerWallet: referrer || this.provider.wallet.publicKey,
       systemProgram: SystemProgram.programId,
+      delegate: await this.delegateFor(agentPda),
+      // Members of an organisation spend its shared budget.
+      organization: (agent.organization as PublicKey | null) ?? null,
     };
 
     const tx = await this.program.methods
@@ -274,6 +465,68 @@ export class AgentMemoryClient {
     return tx;
   }
 
+  /** Start the next model/prompt version; later logs are attributed to it. */
+  async activateAgentVersion(
+    agentId: string,
+    modelHash: Buffer,
+    configHash: Buffer
+  ): Promise<string> {
+    const [agentPda] = this.getAgentPDA(agentId);
+    const agent = await this.program.account.agentAccount.fetch(agentPda);
+    const version = (agent.activeVersion as number) + 1;
+
+    const tx = await this.program.methods
+      .activateAgentVersion(version, Array.from(modelHash), Array.from(configHash))
+      .accounts({
+        agent: agentPda,
+        agentVersion: this.getAgentVersionPDA(agentPda, version)[0],
+        authority: this.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  async initializeOrganization(orgId: string): Promise<string> {
+    const tx = await this.program.methods
+      .initializeOrganization(orgId)
+      .accounts({
+        organization: this.getOrganizationPDA(orgId)[0],
+        admin: this.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  async fundOrganization(orgId: string, lamports: number): Promise<string> {
+    const tx = await this.program.methods
+      .fundOrganization(new BN(lamports))
+      .accounts({
+        organization: this.getOrganizationPDA(orgId)[0],
+        funder: this.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    return tx;
+  }
+
+  async syncOrganizationStats(orgId: string, agentId: string): Promise<string> {
+    const tx = await this.program.methods
+      .syncOrganizationStats()
+      .accounts({
+        organization: this.getOrganizationPDA(orgId)[0],
+        agent: this.getAgentPDA(agentId)[0],
+        platformConfig: this.getPlatformConfigPDA()[0],
+      })
+      .rpc();
+
+    return tx;
+  }
+
   // ============================================================================
   // Read Operations
   // ============================================================================
@@ -286,17 +539,67 @@ export class AgentMemoryClient {
       agentId: account.agentId as string,
       authority: account.authority as PublicKey,
       reputation: (account.reputation as BN).toNumber(),
+      totalPositive: (account.totalPositive as BN).toNumber(),
+      totalNegative: (account.totalNegative as BN).toNumber(),
+      successCount: (account.successCount as BN).toNumber(),
+      failureCount: (account.failureCount as BN).toNumber(),
+      streak: (account.streak as BN).toNumber(),
       totalLogs: (account.totalLogs as BN).toNumber(),
       totalAttestations: (account.totalAttestations as BN).toNumber(),
       bump: account.bump as number,
     };
   }
 
+  /**
+   * The agent's latest reputation checkpoint taken at or before `at`
+   * (a unix timestamp, or a slot when `bySlot` is set). Returns null when
+   * the point predates every retained checkpoint. Reads the account
+   * directly; programs can ask on-chain with `getReputationCheckpointAtSlot`.
+   * History is opt-in, so this throws for agents without a
+   * `ReputationHistory`.
+   */
+  async getReputationCheckpointAt(
+    agentId: string,
+    at: number,
+    bySlot: boolean = false
+  ): Promise<{
+    slot: number;
+    timestamp: number;
+    reputation: number;
+    halfLifeSecs: number;
+  } | null> {
+    const [agentPda] = this.getAgentPDA(agentId);
+    const [historyPda] = this.getReputationHistoryPDA(agentPda);
+    const history = await this.program.account.reputationHistory.fetch(historyPda);
+
+    let best: {
+      slot: number;
+      timestamp: number;
+      reputation: number;
+      halfLifeSecs: number;
+    } | null = null;
+    for (const checkpoint of history.checkpoints as any[]) {
+      const entry = {
+        slot: (checkpoint.slot as BN).toNumber(),
+        timestamp: (checkpoint.timestamp as BN).toNumber(),
+        reputation: (checkpoint.reputation as BN).toNumber(),
+        halfLifeSecs: (checkpoint.halfLifeSecs as BN).toNumber(),
+      };
+      if ((bySlot ? entry.slot : entry.timestamp) > at) continue;
+      // Slots only increase, so the latest checkpoint has the highest slot.
+      if (!best || entry.slot > best.slot) {
+        best = entry;
+      }
+    }
+    return best;
+  }
+
   async getMemoryLog(memoryLogPubkey: PublicKey): Promise<MemoryLogAccount> {
     const account = await this.program.account.memoryLog.fetch(memoryLogPubkey);
 
     return {
       agent: account.agent as PublicKey,
+      sequence: (account.sequence as BN).toNumber(),
       inputHash: account.inputHash as number[],
       logicHash: account.logicHash as number[],
       merkleRoot: account.merkleRoot as number[],
@@ -312,6 +615,7 @@ export class AgentMemoryClient {
 
     return {
       memoryLog: account.memoryLog as PublicKey,
+      attester: account.attester as PublicKey,
       outcomeHash: account.outcomeHash as number[],
       success: account.success as boolean,
       scoreDelta: (account.scoreDelta as BN).toNumber(),
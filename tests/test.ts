@@ -1,6 +1,7 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
 import { PublicKey, SystemProgram, Keypair, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import { keccak_256 } from "@noble/hashes/sha3";
 import { assert } from "chai";
 
 const idl = require("../target/idl/agentmemory.json");
@@ -15,12 +16,58 @@ describe("AgentMemory Protocol", () => {
   const treasury = Keypair.generate();
   const creator = Keypair.generate();
   const buyer = Keypair.generate();
+  const attester = Keypair.generate();
+  const judge = Keypair.generate();
 
   const agentId = "test-agent-001";
   const moduleId = "bitemporal-v1";
   let agentPda: PublicKey;
   let memoryLogPubkey: PublicKey;
 
+  const memoryLogPda = (agent: PublicKey, sequence: number) => {
+    const buf = Buffer.alloc(8);
+    buf.writeBigUInt64LE(BigInt(sequence));
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("memory"), agent.toBuffer(), buf],
+      program.programId
+    );
+  };
+
+  // Off-chain mirror of `merkle.rs`: leaves are keccak(input || logic),
+  // nodes keccak(0x01 || left || right), and an odd last node is promoted.
+  type Leaf = { inputHash: number[]; logicHash: number[] };
+  const hashLeaf = (leaf: Leaf) =>
+    Buffer.from(
+      keccak_256(Buffer.from([...leaf.inputHash, ...leaf.logicHash]))
+    );
+  const hashNode = (left: Buffer, right: Buffer) =>
+    Buffer.from(keccak_256(Buffer.concat([Buffer.from([1]), left, right])));
+  const merkleLevels = (leaves: Leaf[]) => {
+    const levels = [leaves.map(hashLeaf)];
+    while (levels[levels.length - 1].length > 1) {
+      const level = levels[levels.length - 1];
+      const next: Buffer[] = [];
+      for (let i = 0; i < level.length; i += 2) {
+        next.push(i + 1 < level.length ? hashNode(level[i], level[i + 1]) : level[i]);
+      }
+      levels.push(next);
+    }
+    return levels;
+  };
+  const merkleProof = (leaves: Leaf[], index: number) =>
+    merkleLevels(leaves)
+      .slice(0, -1)
+      .flatMap((level, depth) => {
+        const sibling = (index >> depth) ^ 1;
+        return sibling < level.length ? [Array.from(level[sibling])] : [];
+      });
+
+  const nameReservationPda = (name: string) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("name_reservation"), Buffer.from(name)],
+      program.programId
+    );
+
   before(async () => {
     [agentPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("agent"), Buffer.from(agentId)],
@@ -39,6 +86,12 @@ describe("AgentMemory Protocol", () => {
     );
     await provider.connection.confirmTransaction(airdropBuyer);
 
+    const airdropAttester = await provider.connection.requestAirdrop(
+      attester.publicKey,
+      LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdropAttester);
+
     const airdropTreasury = await provider.connection.requestAirdrop(
       treasury.publicKey,
       0.1 * LAMPORTS_PER_SOL
@@ -46,146 +99,1925 @@ describe("AgentMemory Protocol", () => {
     await provider.connection.confirmTransaction(airdropTreasury);
   });
 
-  it("Initializes platform config", async () => {
-    const [configPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("platform_config")],
-      program.programId
-    );
+  it("Initializes platform config", async () => {
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("platform_config")],
+      program.programId
+    );
+
+    await program.methods
+      .initializePlatform(treasury.publicKey, 500, 500)
+      .accounts({
+        platformConfig: configPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const config = await program.account.platformConfig.fetch(configPda);
+    assert.equal(config.platformFeeBps, 500);
+    assert.equal(config.referralFeeBps, 500);
+    assert.equal(
+      (config.treasury as PublicKey).toBase58(),
+      treasury.publicKey.toBase58()
+    );
+    assert.equal(
+      (config.authority as PublicKey).toBase58(),
+      authority.publicKey.toBase58()
+    );
+  });
+
+  it("Initializes an agent", async () => {
+    await program.methods
+      .initializeAgent(agentId)
+      .accounts({
+        agent: agentPda,
+        platformConfig: configPda,
+        nameReservation: nameReservationPda(agentId)[0],
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        organization: null,
+      })
+      .rpc();
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal(agent.agentId, agentId);
+    assert.equal((agent.reputation as BN).toNumber(), 0);
+    assert.equal((agent.totalLogs as BN).toNumber(), 0);
+    assert.equal((agent.totalAttestations as BN).toNumber(), 0);
+  });
+
+  it("Logs a decision", async () => {
+    const inputData = "BTC at $97000, RSI oversold at 28";
+    const logicData = "Apply DCA strategy, buy 0.01 BTC";
+
+    const [memLogPda] = memoryLogPda(agentPda, 0);
+
+    const tx = await program.methods
+      .logDecision(inputData, logicData, "")
+      .accounts({
+        agent: agentPda,
+        memoryLog: memLogPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        delegate: null,
+      })
+      .rpc();
+
+    console.log("Decision logged:", tx);
+
+    const memLog = await program.account.memoryLog.fetch(memLogPda);
+    assert.equal((memLog.agent as PublicKey).toBase58(), agentPda.toBase58());
+    assert.isFalse(memLog.isAttested as boolean);
+    assert.isNotNull(memLog.inputHash);
+    assert.isNotNull(memLog.logicHash);
+    assert.isNotNull(memLog.merkleRoot);
+    assert.equal((memLog.sequence as BN).toNumber(), 0);
+
+    memoryLogPubkey = memLogPda;
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal((agent.totalLogs as BN).toNumber(), 1);
+  });
+
+  it("Logs several decisions in the same second", async () => {
+    for (const seq of [1, 2]) {
+      const [memLogPda] = memoryLogPda(agentPda, seq);
+      await program.methods
+        .logDecision(`tick ${seq}`, "Hold position", "")
+        .accounts({
+          agent: agentPda,
+          memoryLog: memLogPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+          delegate: null,
+        })
+        .rpc();
+
+      const memLog = await program.account.memoryLog.fetch(memLogPda);
+      assert.equal((memLog.sequence as BN).toNumber(), seq);
+    }
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal((agent.totalLogs as BN).toNumber(), 3);
+  });
+
+  it("Logs a batch of decisions under one root", async () => {
+    const agentBefore = await program.account.agentAccount.fetch(agentPda);
+    const firstSequence = (agentBefore.totalLogs as BN).toNumber();
+
+    const seqBuf = Buffer.alloc(8);
+    seqBuf.writeBigUInt64LE(BigInt(firstSequence));
+    const [batchPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("batch"), agentPda.toBuffer(), seqBuf],
+      program.programId
+    );
+
+    const leaves = [1, 2, 3].map((i) => ({
+      inputHash: Array(32).fill(i),
+      logicHash: Array(32).fill(i + 100),
+    }));
+
+    await program.methods
+      .logDecisionBatch(leaves)
+      .accounts({
+        agent: agentPda,
+        batch: batchPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const batch = await program.account.decisionBatch.fetch(batchPda);
+    assert.equal(batch.leafCount, 3);
+    assert.equal((batch.firstSequence as BN).toNumber(), firstSequence);
+    assert.equal((batch.lastSequence as BN).toNumber(), firstSequence + 2);
+    assert.equal(batch.version, agentBefore.activeVersion);
+
+    const levels = merkleLevels(leaves);
+    assert.deepEqual(
+      Array.from(batch.merkleRoot as number[]),
+      Array.from(levels[levels.length - 1][0])
+    );
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal((agent.totalLogs as BN).toNumber(), firstSequence + 3);
+  });
+
+  it("Verifies the hash-chained decision history", async () => {
+    const seqBuf = Buffer.alloc(8);
+    seqBuf.writeBigUInt64LE(BigInt(3));
+    const [batchPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("batch"), agentPda.toBuffer(), seqBuf],
+      program.programId
+    );
+    const entries = [0, 1, 2]
+      .map((seq) => memoryLogPda(agentPda, seq)[0])
+      .concat([batchPda])
+      .map((pubkey) => ({ pubkey, isSigner: false, isWritable: false }));
+
+    await program.methods
+      .verifyLogChain()
+      .accounts({ agent: agentPda })
+      .remainingAccounts(entries)
+      .rpc();
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal((agent.lastLog as PublicKey).toBase58(), batchPda.toBase58());
+
+    try {
+      await program.methods
+        .verifyLogChain()
+        .accounts({ agent: agentPda })
+        .remainingAccounts([entries[0], entries[2]])
+        .rpc();
+      assert.fail("Should have rejected a gap in the chain");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("BrokenLogChain"));
+    }
+  });
+
+  it("Verifies a decision against its memory log", async () => {
+    const memLog = await program.account.memoryLog.fetch(memoryLogPubkey);
+
+    await program.methods
+      .verifyDecisionInclusion(memLog.inputHash, memLog.logicHash, 0, [])
+      .accounts({ memoryLog: memoryLogPubkey, batch: null })
+      .rpc();
+
+    try {
+      await program.methods
+        .verifyDecisionInclusion(memLog.logicHash, memLog.inputHash, 0, [])
+        .accounts({ memoryLog: memoryLogPubkey, batch: null })
+        .rpc();
+      assert.fail("Should have rejected a swapped leaf");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("InvalidInclusionProof"));
+    }
+  });
+
+  it("Verifies a decision inside a batch", async () => {
+    const seqBuf = Buffer.alloc(8);
+    seqBuf.writeBigUInt64LE(BigInt(3));
+    const [batchPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("batch"), agentPda.toBuffer(), seqBuf],
+      program.programId
+    );
+    const leaves = [1, 2, 3].map((i) => ({
+      inputHash: Array(32).fill(i),
+      logicHash: Array(32).fill(i + 100),
+    }));
+    const verify = (index: number, proof: number[][]) =>
+      program.methods
+        .verifyDecisionInclusion(
+          leaves[index].inputHash,
+          leaves[index].logicHash,
+          index,
+          proof
+        )
+        .accounts({ memoryLog: null, batch: batchPda })
+        .rpc();
+
+    // Leaf 0 needs its sibling and the promoted third leaf; leaf 2 was
+    // promoted and only needs the node above the first pair.
+    assert.equal(merkleProof(leaves, 0).length, 2);
+    assert.equal(merkleProof(leaves, 2).length, 1);
+    for (const index of [0, 1, 2]) {
+      await verify(index, merkleProof(leaves, index));
+    }
+
+    const tampered = merkleProof(leaves, 0);
+    tampered[1] = tampered[1].map((byte, i) => (i === 0 ? byte ^ 1 : byte));
+    for (const [index, proof] of [
+      [0, tampered],
+      [1, merkleProof(leaves, 0)],
+      [2, merkleProof(leaves, 0)],
+    ] as [number, number[][]][]) {
+      try {
+        await verify(index, proof);
+        assert.fail("Should have rejected a bad batch proof");
+      } catch (err: any) {
+        assert.ok(err.toString().includes("InvalidInclusionProof"));
+      }
+    }
+  });
+
+  it("Logs a salted commitment and reveals it later", async () => {
+    const inputData = "Private order flow signal: whale accumulating SOL";
+    const logicData = "Front-run avoided, buy after confirmation";
+    const inputSalt = Keypair.generate().secretKey.slice(0, 32);
+    const logicSalt = Keypair.generate().secretKey.slice(0, 32);
+    const commit = (salt: Uint8Array, data: string) =>
+      Array.from(keccak_256(Buffer.concat([salt, Buffer.from(data)])));
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    const [memLogPda] = memoryLogPda(agentPda, (agent.totalLogs as BN).toNumber());
+
+    await program.methods
+      .logDecisionCommitment(
+        commit(inputSalt, inputData),
+        commit(logicSalt, logicData),
+        "",
+        new BN(0),
+        new BN(0)
+      )
+      .accounts({
+        agent: agentPda,
+        memoryLog: memLogPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        delegate: null,
+      })
+      .rpc();
+
+    let memLog = await program.account.memoryLog.fetch(memLogPda);
+    assert.isTrue(memLog.isCommitment as boolean);
+    assert.isFalse(memLog.isRevealed as boolean);
+
+    try {
+      await program.methods
+        .revealDecision(inputData, "something else", Array.from(inputSalt), Array.from(logicSalt))
+        .accounts({ agent: agentPda, memoryLog: memLogPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Should have rejected a mismatched reveal");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("RevealMismatch"));
+    }
+
+    await program.methods
+      .revealDecision(inputData, logicData, Array.from(inputSalt), Array.from(logicSalt))
+      .accounts({ agent: agentPda, memoryLog: memLogPda, authority: authority.publicKey })
+      .rpc();
+
+    memLog = await program.account.memoryLog.fetch(memLogPda);
+    assert.isTrue(memLog.isRevealed as boolean);
+  });
+
+  it("Rejects a reveal before its window opens", async () => {
+    const salt = Keypair.generate().secretKey.slice(0, 32);
+    const prediction = "SOL closes above $250 on Friday";
+    const commitment = Array.from(keccak_256(Buffer.concat([salt, Buffer.from(prediction)])));
+    const now = Math.floor(Date.now() / 1000);
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    const [memLogPda] = memoryLogPda(agentPda, (agent.totalLogs as BN).toNumber());
+
+    await program.methods
+      .logDecisionCommitment(commitment, commitment, "", new BN(now + 3600), new BN(now + 7200))
+      .accounts({
+        agent: agentPda,
+        memoryLog: memLogPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        delegate: null,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .revealDecision(prediction, prediction, Array.from(salt), Array.from(salt))
+        .accounts({ agent: agentPda, memoryLog: memLogPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Should have rejected an early reveal");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("RevealTooEarly"));
+    }
+  });
+
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("platform_config")],
+    program.programId
+  );
+  const attesterPda = (key: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("attester"), key.toBuffer()],
+      program.programId
+    )[0];
+  const vaultPda = (key: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("attester_vault"), key.toBuffer()],
+      program.programId
+    )[0];
+  const allowlistPda = (agent: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("attester_allowlist"), agent.toBuffer()],
+      program.programId
+    )[0];
+
+  // Open a quorum round on `memoryLog` as the platform authority and cast
+  // `votes` into it.
+  const voteRound = async (
+    memoryLog: PublicKey,
+    votes: [Keypair, boolean, number][]
+  ) => {
+    const [round] = PublicKey.findProgramAddressSync(
+      [Buffer.from("round"), memoryLog.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .openAttestationRound(votes.length)
+      .accounts({
+        agent: agentPda,
+        memoryLog,
+        round,
+        platformConfig: configPda,
+        opener: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        openerAttester: null,
+      })
+      .rpc();
+    for (const [voter, success, delta] of votes) {
+      await program.methods
+        .submitRoundVote(success, new BN(delta))
+        .accounts({
+          agent: agentPda,
+          memoryLog,
+          round,
+          attesterAccount: attesterPda(voter.publicKey),
+          attesterAllowlist: allowlistPda(agentPda),
+          attester: voter.publicKey,
+        })
+        .signers([voter])
+        .rpc();
+    }
+  };
+
+  // Settle a round that reached quorum into an attestation, signed by a
+  // majority voter.
+  const settleRound = (
+    memoryLog: PublicKey,
+    settler: Keypair,
+    domainReputation: PublicKey | null = null,
+    reputationHistory: PublicKey | null = null
+  ) =>
+    program.methods
+      .settleAttestationRound()
+      .accounts({
+        agent: agentPda,
+        memoryLog,
+        round: PublicKey.findProgramAddressSync(
+          [Buffer.from("round"), memoryLog.toBuffer()],
+          program.programId
+        )[0],
+        attestation: PublicKey.findProgramAddressSync(
+          [Buffer.from("attest"), memoryLog.toBuffer()],
+          program.programId
+        )[0],
+        attesterAccount: attesterPda(settler.publicKey),
+        platformConfig: configPda,
+        attester: settler.publicKey,
+        systemProgram: SystemProgram.programId,
+        domainReputation,
+        reputationHistory,
+      })
+      .signers([settler])
+      .rpc();
+
+  it("Registers an attester", async () => {
+    for (const key of [attester.publicKey, authority.publicKey]) {
+      await program.methods
+        .registerAttester(key)
+        .accounts({
+          platformConfig: configPda,
+          attesterAccount: attesterPda(key),
+          attesterVault: vaultPda(key),
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    const account = await program.account.attesterAccount.fetch(
+      attesterPda(attester.publicKey)
+    );
+    assert.isTrue(account.isActive as boolean);
+  });
+
+  it("Bonds an attester", async () => {
+    const bond = 0.2 * LAMPORTS_PER_SOL;
+
+    await program.methods
+      .depositAttesterBond(new BN(bond))
+      .accounts({
+        attesterAccount: attesterPda(attester.publicKey),
+        attesterVault: vaultPda(attester.publicKey),
+        attester: attester.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([attester])
+      .rpc();
+
+    const account = await program.account.attesterAccount.fetch(
+      attesterPda(attester.publicKey)
+    );
+    assert.equal((account.bondLamports as BN).toNumber(), bond);
+  });
+
+  it("Rejects self-attestation", async () => {
+    const [attestPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("attest"), memoryLogPubkey.toBuffer()],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .attestOutcome("I did great", true, new BN(100))
+        .accounts({
+          agent: agentPda,
+          memoryLog: memoryLogPubkey,
+          attestation: attestPda,
+          attesterAccount: attesterPda(authority.publicKey),
+          attesterAllowlist: allowlistPda(agentPda),
+          platformConfig: configPda,
+          attester: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+          domainReputation: null,
+          reputationHistory: null,
+          attesterAgent: null,
+        })
+        .rpc();
+      assert.fail("Should have rejected self-attestation");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("SelfAttestation"));
+    }
+  });
+
+  it("Attests an outcome", async () => {
+    const outcomeData = "Bought at $97050, profit +2.3% after 4h";
+    const success = true;
+    const scoreDelta = 10;
+
+    const [attestPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("attest"), memoryLogPubkey.toBuffer()],
+      program.programId
+    );
+
+    const tx = await program.methods
+      .attestOutcome(outcomeData, success, new BN(scoreDelta))
+      .accounts({
+        agent: agentPda,
+        memoryLog: memoryLogPubkey,
+        attestation: attestPda,
+        attesterAccount: attesterPda(attester.publicKey),
+        attesterAllowlist: allowlistPda(agentPda),
+        platformConfig: configPda,
+        attester: attester.publicKey,
+        systemProgram: SystemProgram.programId,
+        domainReputation: null,
+        reputationHistory: null,
+        attesterAgent: null,
+      })
+      .signers([attester])
+      .rpc();
+
+    console.log("Outcome attested:", tx);
+
+    const attestation = await program.account.attestation.fetch(attestPda);
+    assert.equal(
+      (attestation.attester as PublicKey).toBase58(),
+      attester.publicKey.toBase58()
+    );
+    assert.isTrue(attestation.success as boolean);
+    assert.equal((attestation.scoreDelta as BN).toNumber(), scoreDelta);
+    assert.deepEqual(attestation.status, { pending: {} });
+
+    const memLog = await program.account.memoryLog.fetch(memoryLogPubkey);
+    assert.isTrue(memLog.isAttested as boolean);
+
+    // Reputation and the attestation count only move once the dispute
+    // window is over.
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal((agent.reputation as BN).toNumber(), 0);
+    assert.equal((agent.totalAttestations as BN).toNumber(), 0);
+  });
+
+  it("Arbitrates a disputed attestation", async () => {
+    const [attestPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("attest"), memoryLogPubkey.toBuffer()],
+      program.programId
+    );
+    const [disputePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("dispute"), attestPda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .disputeAttestation(Array(32).fill(3))
+      .accounts({
+        agent: agentPda,
+        memoryLog: memoryLogPubkey,
+        attestation: attestPda,
+        dispute: disputePda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    let attestation = await program.account.attestation.fetch(attestPda);
+    assert.deepEqual(attestation.status, { disputed: {} });
+
+    // The platform authority is the default arbiter.
+    const resolveAccounts = {
+      platformConfig: configPda,
+      agent: agentPda,
+      memoryLog: memoryLogPubkey,
+      attestation: attestPda,
+      dispute: disputePda,
+      arbiter: authority.publicKey,
+      domainReputation: null,
+      reputationHistory: null,
+    };
+
+    // Scoring is held while the agent is frozen and resumes afterwards.
+    const evidence = Array.from(Buffer.alloc(32, 5));
+    const moderateAccounts = {
+      agent: agentPda,
+      platformConfig: configPda,
+      authority: authority.publicKey,
+    };
+    await program.methods.freezeAgent(1, evidence).accounts(moderateAccounts).rpc();
+    try {
+      await program.methods.resolveDispute(true).accounts(resolveAccounts).rpc();
+      assert.fail("Should have held the outcome while the agent is frozen");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("AgentFrozen"));
+    }
+    await program.methods.unfreezeAgent(0, evidence).accounts(moderateAccounts).rpc();
+
+    await program.methods.resolveDispute(true).accounts(resolveAccounts).rpc();
+
+    attestation = await program.account.attestation.fetch(attestPda);
+    assert.deepEqual(attestation.status, { finalized: {} });
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal((agent.reputation as BN).toNumber(), 10);
+    assert.equal((agent.totalAttestations as BN).toNumber(), 1);
+  });
+
+  it("Prevents double attestation", async () => {
+    const [attestPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("attest"), memoryLogPubkey.toBuffer()],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .attestOutcome("duplicate", true, new BN(5))
+        .accounts({
+          agent: agentPda,
+          memoryLog: memoryLogPubkey,
+          attestation: attestPda,
+          attesterAccount: attesterPda(attester.publicKey),
+          attesterAllowlist: allowlistPda(agentPda),
+          platformConfig: configPda,
+          attester: attester.publicKey,
+          systemProgram: SystemProgram.programId,
+          domainReputation: null,
+          reputationHistory: null,
+          attesterAgent: null,
+        })
+        .signers([attester])
+        .rpc();
+      assert.fail("Should have thrown");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("already in use") || err.toString().includes("AlreadyAttested"));
+    }
+  });
+
+  it("Slashes an attester when a challenge is upheld", async () => {
+    const [attestPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("attest"), memoryLogPubkey.toBuffer()],
+      program.programId
+    );
+    const [challengePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("challenge"), attestPda.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .challengeAttestation(Array(32).fill(7))
+      .accounts({
+        attestation: attestPda,
+        attesterAccount: attesterPda(attester.publicKey),
+        challenge: challengePda,
+        challenger: buyer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([buyer])
+      .rpc();
+
+    const bondBefore = (
+      await program.account.attesterAccount.fetch(attesterPda(attester.publicKey))
+    ).bondLamports as BN;
+
+    await program.methods
+      .resolveChallenge(true)
+      .accounts({
+        platformConfig: configPda,
+        challenge: challengePda,
+        attestation: attestPda,
+        memoryLog: memoryLogPubkey,
+        agent: agentPda,
+        attesterAccount: attesterPda(attester.publicKey),
+        attesterVault: vaultPda(attester.publicKey),
+        attesterWallet: attester.publicKey,
+        challenger: buyer.publicKey,
+        treasury: treasury.publicKey,
+        authority: authority.publicKey,
+        domainReputation: null,
+        reputationHistory: null,
+      })
+      .rpc();
+
+    const account = await program.account.attesterAccount.fetch(
+      attesterPda(attester.publicKey)
+    );
+    assert.equal((account.bondLamports as BN).toNumber(), bondBefore.toNumber() / 2);
+    assert.equal(account.openChallenges, 0);
+
+    const attestation = await program.account.attestation.fetch(attestPda);
+    assert.deepEqual(attestation.status, { rejected: {} });
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal((agent.reputation as BN).toNumber(), 0);
+
+    // Top the bond back up for later tests.
+    await program.methods
+      .depositAttesterBond(bondBefore.divn(2))
+      .accounts({
+        attesterAccount: attesterPda(attester.publicKey),
+        attesterVault: vaultPda(attester.publicKey),
+        attester: attester.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([attester])
+      .rpc();
+  });
+
+  it("Settles a quorum attestation round", async () => {
+    const airdrop = await provider.connection.requestAirdrop(
+      judge.publicKey,
+      LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdrop);
+
+    await program.methods
+      .registerAttester(judge.publicKey)
+      .accounts({
+        platformConfig: configPda,
+        attesterAccount: attesterPda(judge.publicKey),
+        attesterVault: vaultPda(judge.publicKey),
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .depositAttesterBond(new BN(0.1 * LAMPORTS_PER_SOL))
+      .accounts({
+        attesterAccount: attesterPda(judge.publicKey),
+        attesterVault: vaultPda(judge.publicKey),
+        attester: judge.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([judge])
+      .rpc();
+
+    const before = await program.account.agentAccount.fetch(agentPda);
+    const [memLogPda] = memoryLogPda(agentPda, (before.totalLogs as BN).toNumber());
+    await program.methods
+      .logDecision("ETH/BTC ratio breakout", "Rotate 10% into ETH", "")
+      .accounts({
+        agent: agentPda,
+        memoryLog: memLogPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        delegate: null,
+      })
+      .rpc();
+
+    const [roundPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("round"), memLogPda.toBuffer()],
+      program.programId
+    );
+    const openAccounts = {
+      agent: agentPda,
+      memoryLog: memLogPda,
+      round: roundPda,
+      platformConfig: configPda,
+      systemProgram: SystemProgram.programId,
+    };
+
+    try {
+      await program.methods
+        .openAttestationRound(2)
+        .accounts({ ...openAccounts, opener: buyer.publicKey, openerAttester: null })
+        .signers([buyer])
+        .rpc();
+      assert.fail("Should have required the platform authority or an attester");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("UnauthorizedRoundOpener"));
+    }
+
+    await program.methods
+      .openAttestationRound(2)
+      .accounts({ ...openAccounts, opener: authority.publicKey, openerAttester: null })
+      .rpc();
+
+    for (const [voter, delta] of [
+      [attester, 20],
+      [judge, 10],
+    ] as [Keypair, number][]) {
+      await program.methods
+        .submitRoundVote(true, new BN(delta))
+        .accounts({
+          agent: agentPda,
+          memoryLog: memLogPda,
+          round: roundPda,
+          attesterAccount: attesterPda(voter.publicKey),
+          attesterAllowlist: allowlistPda(agentPda),
+          attester: voter.publicKey,
+        })
+        .signers([voter])
+        .rpc();
+    }
+
+    const [attestPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("attest"), memLogPda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .settleAttestationRound()
+      .accounts({
+        agent: agentPda,
+        memoryLog: memLogPda,
+        round: roundPda,
+        attestation: attestPda,
+        attesterAccount: attesterPda(attester.publicKey),
+        platformConfig: configPda,
+        attester: attester.publicKey,
+        systemProgram: SystemProgram.programId,
+        domainReputation: null,
+        reputationHistory: null,
+      })
+      .signers([attester])
+      .rpc();
+
+    const round = await program.account.attestationRound.fetch(roundPda);
+    assert.isTrue(round.isFinalized as boolean);
+    assert.equal((round.scoreDelta as BN).toNumber(), 15);
+
+    // The settled result is an ordinary pending attestation.
+    let attestation = await program.account.attestation.fetch(attestPda);
+    assert.deepEqual(attestation.status, { pending: {} });
+    assert.equal((attestation.scoreDelta as BN).toNumber(), 15);
+    assert.equal(
+      (attestation.attester as PublicKey).toBase58(),
+      attester.publicKey.toBase58()
+    );
+
+    try {
+      await program.methods
+        .cancelAttestationRound()
+        .accounts({
+          memoryLog: memLogPda,
+          round: roundPda,
+          opener: authority.publicKey,
+        })
+        .rpc();
+      assert.fail("Should not cancel a round that reached quorum");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("RoundNotExpired"));
+    }
+
+    const [disputePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("dispute"), attestPda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .disputeAttestation(Array(32).fill(4))
+      .accounts({
+        agent: agentPda,
+        memoryLog: memLogPda,
+        attestation: attestPda,
+        dispute: disputePda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .challengeAttestation(Array(32).fill(8))
+        .accounts({
+          attestation: attestPda,
+          attesterAccount: attesterPda(attester.publicKey),
+          challenge: PublicKey.findProgramAddressSync(
+            [Buffer.from("challenge"), attestPda.toBuffer()],
+            program.programId
+          )[0],
+          challenger: buyer.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([buyer])
+        .rpc();
+      assert.fail("Should not challenge an attestation under dispute");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("DisputeStillOpen"));
+    }
+
+    await program.methods
+      .resolveDispute(true)
+      .accounts({
+        platformConfig: configPda,
+        agent: agentPda,
+        memoryLog: memLogPda,
+        attestation: attestPda,
+        dispute: disputePda,
+        arbiter: authority.publicKey,
+        domainReputation: null,
+        reputationHistory: null,
+      })
+      .rpc();
+
+    attestation = await program.account.attestation.fetch(attestPda);
+    assert.deepEqual(attestation.status, { finalized: {} });
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal(
+      (agent.reputation as BN).toNumber(),
+      (before.reputation as BN).toNumber() + 15
+    );
+  });
+
+  it("Decays reputation by the configured half-life", async () => {
+    const before = await program.account.agentAccount.fetch(agentPda);
+
+    await program.methods
+      .setReputationDecay(new BN(30 * 24 * 60 * 60))
+      .accounts({ platformConfig: configPda, authority: authority.publicKey })
+      .rpc();
+
+    await program.methods
+      .refreshReputation()
+      .accounts({
+        agent: agentPda,
+        platformConfig: configPda,
+        reputationHistory: null,
+      })
+      .rpc();
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.isAtMost(
+      (agent.reputation as BN).toNumber(),
+      (before.reputation as BN).toNumber()
+    );
+    assert.isAbove(
+      (agent.lastReputationUpdate as BN).toNumber(),
+      (before.lastReputationUpdate as BN).toNumber() - 1
+    );
+
+    await program.methods
+      .setReputationDecay(new BN(0))
+      .accounts({ platformConfig: configPda, authority: authority.publicKey })
+      .rpc();
+  });
+
+  it("Tracks reputation per domain", async () => {
+    // Apply settled rounds straight away for the reputation tests below.
+    await program.methods
+      .setDisputeConfig(authority.publicKey, new BN(0))
+      .accounts({ platformConfig: configPda, authority: authority.publicKey })
+      .rpc();
+
+    const domain = "trading";
+    const [domainRepPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("domain_rep"), agentPda.toBuffer(), Buffer.from(domain)],
+      program.programId
+    );
+    await program.methods
+      .initializeDomainReputation(domain)
+      .accounts({
+        agent: agentPda,
+        domainReputation: domainRepPda,
+        payer: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const before = await program.account.agentAccount.fetch(agentPda);
+    const [memLogPda] = memoryLogPda(agentPda, (before.totalLogs as BN).toNumber());
+    await program.methods
+      .logDecision("SOL funding rate negative", "Open small long", domain)
+      .accounts({
+        agent: agentPda,
+        memoryLog: memLogPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        delegate: null,
+      })
+      .rpc();
+
+    await voteRound(memLogPda, [
+      [attester, true, 8],
+      [judge, true, 8],
+    ]);
+    try {
+      await settleRound(memLogPda, attester);
+      assert.fail("Should have required the domain reputation account");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("MissingDomainReputation"));
+    }
+    await settleRound(memLogPda, attester, domainRepPda);
+
+    const domainRep = await program.account.domainReputation.fetch(domainRepPda);
+    assert.equal(domainRep.domain, domain);
+    assert.equal((domainRep.reputation as BN).toNumber(), 8);
+    assert.equal((domainRep.totalAttestations as BN).toNumber(), 1);
+    assert.equal((domainRep.successCount as BN).toNumber(), 1);
+    assert.equal((domainRep.failureCount as BN).toNumber(), 0);
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal(
+      (agent.reputation as BN).toNumber(),
+      (before.reputation as BN).toNumber() + 8
+    );
+  });
+
+  it("Keeps negative reputation below zero", async () => {
+    const before = await program.account.agentAccount.fetch(agentPda);
+    const [memLogPda] = memoryLogPda(agentPda, (before.totalLogs as BN).toNumber());
+    await program.methods
+      .logDecision("Exchange halts withdrawals", "Hold funds on exchange", "")
+      .accounts({
+        agent: agentPda,
+        memoryLog: memLogPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        delegate: null,
+      })
+      .rpc();
+
+    await voteRound(memLogPda, [
+      [attester, false, -1000],
+      [judge, false, -1000],
+    ]);
+    await settleRound(memLogPda, attester);
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal(
+      (agent.reputation as BN).toNumber(),
+      (before.reputation as BN).toNumber() - 1000
+    );
+    assert.isBelow((agent.reputation as BN).toNumber(), 0);
+    assert.equal(
+      (agent.totalNegative as BN).toNumber(),
+      (before.totalNegative as BN).toNumber() + 1000
+    );
+    assert.equal(
+      (agent.failureCount as BN).toNumber(),
+      (before.failureCount as BN).toNumber() + 1
+    );
+    assert.equal((agent.streak as BN).toNumber(), -1);
+  });
+
+  it("Caps score deltas under the capped scoring model", async () => {
+    await program.methods
+      .setScoringModel({ capped: { maxDelta: new BN(25) } })
+      .accounts({ platformConfig: configPda, authority: authority.publicKey })
+      .rpc();
+
+    try {
+      await program.methods
+        .setScoringModel({ elo: { kFactor: 0 } })
+        .accounts({ platformConfig: configPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Should have rejected a zero K-factor");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("InvalidScoringModel"));
+    }
+
+    const before = await program.account.agentAccount.fetch(agentPda);
+    const [memLogPda] = memoryLogPda(agentPda, (before.totalLogs as BN).toNumber());
+    await program.methods
+      .logDecision("Liquidity returns to exchange", "Withdraw to cold storage", "")
+      .accounts({
+        agent: agentPda,
+        memoryLog: memLogPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        delegate: null,
+      })
+      .rpc();
+
+    await voteRound(memLogPda, [
+      [attester, true, 500],
+      [judge, true, 500],
+    ]);
+    await settleRound(memLogPda, attester);
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal(
+      (agent.reputation as BN).toNumber(),
+      (before.reputation as BN).toNumber() + 25
+    );
+
+
+    // Switching to Beta-Bernoulli rescores from the outcome counts rather
+    // than carrying the running score over.
+    await program.methods
+      .setScoringModel({ betaBernoulli: { priorAlpha: 1, priorBeta: 1 } })
+      .accounts({ platformConfig: configPda, authority: authority.publicKey })
+      .rpc();
+    await program.methods
+      .refreshReputation()
+      .accounts({
+        agent: agentPda,
+        platformConfig: configPda,
+        reputationHistory: null,
+      })
+      .rpc();
+    const rescored = await program.account.agentAccount.fetch(agentPda);
+    const successes = (rescored.successCount as BN).toNumber();
+    const failures = (rescored.failureCount as BN).toNumber();
+    assert.equal(
+      (rescored.reputation as BN).toNumber(),
+      Math.floor(((1 + successes) * 10_000) / (2 + successes + failures))
+    );
+
+    await program.methods
+      .setScoringModel({ raw: {} })
+      .accounts({ platformConfig: configPda, authority: authority.publicKey })
+      .rpc();
+  });
+
+  it("Checkpoints reputation changes into the history", async () => {
+    const [historyPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("reputation_history"), agentPda.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .initializeReputationHistory()
+      .accounts({
+        agent: agentPda,
+        reputationHistory: historyPda,
+        platformConfig: configPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const before = await program.account.agentAccount.fetch(agentPda);
+    const [memLogPda] = memoryLogPda(agentPda, (before.totalLogs as BN).toNumber());
+    await program.methods
+      .logDecision("Stablecoin depeg to 0.97", "Buy the dip", "")
+      .accounts({
+        agent: agentPda,
+        memoryLog: memLogPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        delegate: null,
+      })
+      .rpc();
+
+    await voteRound(memLogPda, [
+      [attester, true, 12],
+      [judge, true, 12],
+    ]);
+    try {
+      await settleRound(memLogPda, attester);
+      assert.fail("Should have required the reputation history");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("MissingReputationHistory"));
+    }
+    await settleRound(memLogPda, attester, null, historyPda);
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    const history = await program.account.reputationHistory.fetch(historyPda);
+    const checkpoints = history.checkpoints as any[];
+    assert.equal(checkpoints.length, 2);
+    assert.equal(
+      (checkpoints[0].reputation as BN).toNumber(),
+      (before.reputation as BN).toNumber()
+    );
+    assert.equal(
+      (checkpoints[1].reputation as BN).toNumber(),
+      (agent.reputation as BN).toNumber()
+    );
+
+    const atSlot = await program.methods
+      .getReputationCheckpointAtSlot(checkpoints[1].slot)
+      .accounts({ reputationHistory: historyPda })
+      .view();
+    assert.equal(
+      (atSlot.reputation as BN).toNumber(),
+      (agent.reputation as BN).toNumber()
+    );
+    try {
+      await program.methods
+        .getReputationCheckpointAtSlot(new BN(0))
+        .accounts({ reputationHistory: historyPda })
+        .rpc();
+      assert.fail("Should have found no checkpoint before the history began");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("ReputationCheckpointNotFound"));
+    }
+
+    // A rescore jump is checkpointed with the half-life it decays by.
+    await program.methods
+      .setScoringModel({ betaBernoulli: { priorAlpha: 1, priorBeta: 1 } })
+      .accounts({ platformConfig: configPda, authority: authority.publicKey })
+      .rpc();
+    try {
+      await program.methods
+        .refreshReputation()
+        .accounts({
+          agent: agentPda,
+          platformConfig: configPda,
+          reputationHistory: null,
+        })
+        .rpc();
+      assert.fail("Should have required the reputation history");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("MissingReputationHistory"));
+    }
+    await program.methods
+      .refreshReputation()
+      .accounts({
+        agent: agentPda,
+        platformConfig: configPda,
+        reputationHistory: historyPda,
+      })
+      .rpc();
+    const rescored = await program.account.agentAccount.fetch(agentPda);
+    const rescoredHistory =
+      await program.account.reputationHistory.fetch(historyPda);
+    const latest = (rescoredHistory.checkpoints as any[])[2];
+    assert.equal(
+      (latest.reputation as BN).toNumber(),
+      (rescored.reputation as BN).toNumber()
+    );
+    assert.equal((latest.halfLifeSecs as BN).toNumber(), 0);
+
+    await program.methods
+      .setScoringModel({ raw: {} })
+      .accounts({ platformConfig: configPda, authority: authority.publicKey })
+      .rpc();
+  });
+
+  it("Gates on minimum reputation standing", async () => {
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    const requirements = {
+      minReputation: new BN(-1_000_000),
+      minOutcomes: new BN(1),
+      minSuccessRateBps: 0,
+      minAccountAgeSecs: new BN(0),
+    };
+
+    const standing = await program.methods
+      .requireReputation(requirements)
+      .accounts({ agent: agentPda, platformConfig: configPda })
+      .view();
+    assert.equal(
+      (standing.outcomes as BN).toNumber(),
+      (agent.successCount as BN).toNumber() + (agent.failureCount as BN).toNumber()
+    );
+
+    try {
+      await program.methods
+        .requireReputation({ ...requirements, minSuccessRateBps: 10_000 })
+        .accounts({ agent: agentPda, platformConfig: configPda })
+        .rpc();
+      assert.fail("Should have rejected an imperfect success rate");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("SuccessRateTooLow"));
+    }
+
+    const moderateAccounts = {
+      agent: agentPda,
+      platformConfig: configPda,
+      authority: authority.publicKey,
+    };
+    const evidence = Array.from(Buffer.alloc(32, 6));
+    await program.methods.freezeAgent(2, evidence).accounts(moderateAccounts).rpc();
+    try {
+      await program.methods
+        .requireReputation(requirements)
+        .accounts({ agent: agentPda, platformConfig: configPda })
+        .rpc();
+      assert.fail("Should have rejected a frozen agent");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("AgentFrozen"));
+    }
+    await program.methods.unfreezeAgent(0, evidence).accounts(moderateAccounts).rpc();
+  });
+
+  it("Endorses and un-endorses another agent", async () => {
+    const [historyPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("reputation_history"), agentPda.toBuffer()],
+      program.programId
+    );
+
+    // Lift the endorser's reputation well above zero first.
+    const before = await program.account.agentAccount.fetch(agentPda);
+    const [memLogPda] = memoryLogPda(agentPda, (before.totalLogs as BN).toNumber());
+    await program.methods
+      .logDecision("Funding flips positive", "Close hedge", "")
+      .accounts({
+        agent: agentPda,
+        memoryLog: memLogPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        delegate: null,
+      })
+      .rpc();
+    await voteRound(memLogPda, [
+      [attester, true, 5000],
+      [judge, true, 5000],
+    ]);
+    await settleRound(memLogPda, attester, null, historyPda);
+
+    const endorseeId = "endorsed-agent-001";
+    const [endorseePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("agent"), Buffer.from(endorseeId)],
+      program.programId
+    );
+    await program.methods
+      .initializeAgent(endorseeId)
+      .accounts({
+        agent: endorseePda,
+        platformConfig: configPda,
+        nameReservation: nameReservationPda(endorseeId)[0],
+        authority: buyer.publicKey,
+        systemProgram: SystemProgram.programId,
+        organization: null,
+      })
+      .signers([buyer])
+      .rpc();
+
+    const endorsementPda = (endorser: PublicKey, endorsee: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("endorsement"), endorser.toBuffer(), endorsee.toBuffer()],
+        program.programId
+      )[0];
+
+    try {
+      await program.methods
+        .endorseAgent()
+        .accounts({
+          endorser: agentPda,
+          endorsee: agentPda,
+          endorsement: endorsementPda(agentPda, agentPda),
+          platformConfig: configPda,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Should have rejected a self-endorsement");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("SelfEndorsement"));
+    }
+
+    await program.methods
+      .endorseAgent()
+      .accounts({
+        endorser: agentPda,
+        endorsee: endorseePda,
+        endorsement: endorsementPda(agentPda, endorseePda),
+        platformConfig: configPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const endorser = await program.account.agentAccount.fetch(agentPda);
+    const endorsement = await program.account.endorsement.fetch(
+      endorsementPda(agentPda, endorseePda)
+    );
+    assert.equal(
+      (endorsement.weight as BN).toNumber(),
+      (endorser.reputation as BN).toNumber()
+    );
+    let endorsee = await program.account.agentAccount.fetch(endorseePda);
+    assert.equal(
+      (endorsee.endorsementScore as BN).toNumber(),
+      (endorsement.weight as BN).toNumber()
+    );
+    assert.equal(endorsee.endorsementCount, 1);
+
+    // A frozen endorser's backing drops to nothing until it is restored.
+    const refresh = () =>
+      program.methods
+        .refreshEndorsement()
+        .accounts({
+          endorser: agentPda,
+          endorsee: endorseePda,
+          endorsement: endorsementPda(agentPda, endorseePda),
+          platformConfig: configPda,
+        })
+        .rpc();
+    const moderateAccounts = {
+      agent: agentPda,
+      platformConfig: configPda,
+      authority: authority.publicKey,
+    };
+    const evidence = Array.from(Buffer.alloc(32, 5));
+    await program.methods.freezeAgent(1, evidence).accounts(moderateAccounts).rpc();
+    await refresh();
+    endorsee = await program.account.agentAccount.fetch(endorseePda);
+    assert.equal((endorsee.endorsementScore as BN).toNumber(), 0);
+    assert.equal(endorsee.endorsementCount, 1);
+
+    await program.methods.unfreezeAgent(0, evidence).accounts(moderateAccounts).rpc();
+    await refresh();
+    endorsee = await program.account.agentAccount.fetch(endorseePda);
+    assert.equal(
+      (endorsee.endorsementScore as BN).toNumber(),
+      (endorser.reputation as BN).toNumber()
+    );
+
+    await program.methods
+      .revokeEndorsement()
+      .accounts({
+        endorser: agentPda,
+        endorsee: endorseePda,
+        endorsement: endorsementPda(agentPda, endorseePda),
+        authority: authority.publicKey,
+      })
+      .rpc();
+
+    endorsee = await program.account.agentAccount.fetch(endorseePda);
+    assert.equal((endorsee.endorsementScore as BN).toNumber(), 0);
+    assert.equal(endorsee.endorsementCount, 0);
+
+    await program.methods
+      .setDisputeConfig(authority.publicKey, new BN(3 * 24 * 60 * 60))
+      .accounts({ platformConfig: configPda, authority: authority.publicKey })
+      .rpc();
+  });
+
+  it("Transfers and recovers agent authority", async () => {
+    const [endorseePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("agent"), Buffer.from("endorsed-agent-001")],
+      program.programId
+    );
+
+    await program.methods
+      .proposeAuthorityTransfer(creator.publicKey)
+      .accounts({ agent: endorseePda, authority: buyer.publicKey })
+      .signers([buyer])
+      .rpc();
+
+    try {
+      await program.methods
+        .acceptAuthorityTransfer()
+        .accounts({ agent: endorseePda, newAuthority: treasury.publicKey })
+        .signers([treasury])
+        .rpc();
+      assert.fail("Should have rejected a non-pending authority");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("NotPendingAuthority"));
+    }
+
+    await program.methods
+      .acceptAuthorityTransfer()
+      .accounts({ agent: endorseePda, newAuthority: creator.publicKey })
+      .signers([creator])
+      .rpc();
+
+    let agent = await program.account.agentAccount.fetch(endorseePda);
+    assert.ok((agent.authority as PublicKey).equals(creator.publicKey));
+    assert.isNull(agent.pendingAuthority);
+
+    await program.methods
+      .setRecoveryAuthority(treasury.publicKey)
+      .accounts({
+        agent: endorseePda,
+        authority: creator.publicKey,
+        recoveryAuthority: null,
+      })
+      .signers([creator])
+      .rpc();
+
+    // The authority alone can no longer clear the recovery key.
+    try {
+      await program.methods
+        .setRecoveryAuthority(null)
+        .accounts({
+          agent: endorseePda,
+          authority: creator.publicKey,
+          recoveryAuthority: null,
+        })
+        .signers([creator])
+        .rpc();
+      assert.fail("Should have required the recovery key");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("NotRecoveryAuthority"));
+    }
+
+    const sessionKey = Keypair.generate();
+    const airdrop = await provider.connection.requestAirdrop(
+      sessionKey.publicKey,
+      0.1 * LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(airdrop);
+    const [delegatePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("delegate"), endorseePda.toBuffer(), sessionKey.publicKey.toBuffer()],
+      program.programId
+    );
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .grantDelegate(sessionKey.publicKey, new BN(now + 3600), new BN(5), false)
+      .accounts({
+        agent: endorseePda,
+        delegate: delegatePda,
+        authority: creator.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([creator])
+      .rpc();
+
+    await program.methods
+      .recoverAuthority(buyer.publicKey)
+      .accounts({ agent: endorseePda, recoveryAuthority: treasury.publicKey })
+      .signers([treasury])
+      .rpc();
+
+    agent = await program.account.agentAccount.fetch(endorseePda);
+    assert.ok((agent.authority as PublicKey).equals(buyer.publicKey));
+
+    // Session keys granted before the recovery no longer work.
+    try {
+      await program.methods
+        .logDecision("Stale session", "Should fail", "")
+        .accounts({
+          agent: endorseePda,
+          memoryLog: memoryLogPda(endorseePda, (agent.totalLogs as BN).toNumber())[0],
+          authority: sessionKey.publicKey,
+          systemProgram: SystemProgram.programId,
+          delegate: delegatePda,
+        })
+        .signers([sessionKey])
+        .rpc();
+      assert.fail("Should have rejected a delegate from before recovery");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("StaleDelegate"));
+    }
+  });
+
+  it("Logs through a delegated session key", async () => {
+    const sessionKey = Keypair.generate();
+    const [delegatePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("delegate"), agentPda.toBuffer(), sessionKey.publicKey.toBuffer()],
+      program.programId
+    );
+    const now = Math.floor(Date.now() / 1000);
+
+    await program.methods
+      .grantDelegate(sessionKey.publicKey, new BN(now + 3600), new BN(1), false)
+      .accounts({
+        agent: agentPda,
+        delegate: delegatePda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const logAs = async (signer: Keypair, delegate: PublicKey | null) => {
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      const [memLogPda] = memoryLogPda(agentPda, (agent.totalLogs as BN).toNumber());
+      await program.methods
+        .logDecision("Session heartbeat", "No action", "")
+        .accounts({
+          agent: agentPda,
+          memoryLog: memLogPda,
+          authority: signer.publicKey,
+          systemProgram: SystemProgram.programId,
+          delegate,
+        })
+        .signers([signer])
+        .rpc();
+    };
+
+    // The session key pays for the log account itself.
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(sessionKey.publicKey, LAMPORTS_PER_SOL)
+    );
+    try {
+      await logAs(sessionKey, null);
+      assert.fail("Should have rejected an ungranted signer");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("UnauthorizedSigner"));
+    }
+
+    await logAs(sessionKey, delegatePda);
+
+    const grant = await program.account.delegate.fetch(delegatePda);
+    assert.equal((grant.logsUsed as BN).toNumber(), 1);
+
+    try {
+      await logAs(sessionKey, delegatePda);
+      assert.fail("Should have enforced the log limit");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("DelegateLogLimitReached"));
+    }
+
+    await program.methods
+      .revokeDelegate()
+      .accounts({ agent: agentPda, delegate: delegatePda, authority: authority.publicKey })
+      .rpc();
+  });
+
+  it("Updates the agent profile and resizes the account", async () => {
+    const profile = {
+      displayName: "DCA Bot",
+      description: "Dollar-cost averages into BTC on oversold signals",
+      metadataUri: "ipfs://QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
+      metadataHash: Array.from(keccak_256(Buffer.from("metadata"))),
+      capabilities: new BN(0b101),
+      operatorContactHash: Array.from(keccak_256(Buffer.from("ops@example.com"))),
+      model: "llama-3/eliza",
+    };
+    const sizeBefore = (await provider.connection.getAccountInfo(agentPda))!.data.length;
+
+    await program.methods
+      .updateAgentProfile(profile)
+      .accounts({
+        agent: agentPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const grown = (await provider.connection.getAccountInfo(agentPda))!;
+    const textLen =
+      profile.displayName.length +
+      profile.description.length +
+      profile.metadataUri.length +
+      profile.model.length;
+    assert.equal(grown.data.length, sizeBefore + textLen);
+
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal((agent.profile as any).displayName, profile.displayName);
+
+    await program.methods
+      .updateAgentProfile({ ...profile, description: "", metadataUri: "" })
+      .accounts({
+        agent: agentPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    const shrunk = (await provider.connection.getAccountInfo(agentPda))!;
+    assert.isBelow(shrunk.data.length, grown.data.length);
+    assert.isBelow(shrunk.lamports, grown.lamports);
+  });
+
+  it("Retires an agent and holds its logs for the retention period", async () => {
+    const [retiredPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("agent"), Buffer.from("endorsed-agent-001")],
+      program.programId
+    );
+    const [memLogPda] = memoryLogPda(retiredPda, 0);
+    const logAccounts = {
+      agent: retiredPda,
+      memoryLog: memLogPda,
+      authority: buyer.publicKey,
+      systemProgram: SystemProgram.programId,
+      delegate: null,
+    };
+
+    await program.methods
+      .logDecision("Final check", "Shut down", "")
+      .accounts(logAccounts)
+      .signers([buyer])
+      .rpc();
+
+    await program.methods
+      .closeAgent()
+      .accounts({ agent: retiredPda, authority: buyer.publicKey })
+      .signers([buyer])
+      .rpc();
+
+    const agent = await program.account.agentAccount.fetch(retiredPda);
+    assert.isTrue(agent.isRetired as boolean);
+
+    try {
+      await program.methods
+        .logDecision("After shutdown", "Should fail", "")
+        .accounts({ ...logAccounts, memoryLog: memoryLogPda(retiredPda, 1)[0] })
+        .signers([buyer])
+        .rpc();
+      assert.fail("Should have rejected a log on a retired agent");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("AgentRetired"));
+    }
+
+    const [attestPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("attest"), memLogPda.toBuffer()],
+      program.programId
+    );
+    const [roundPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("round"), memLogPda.toBuffer()],
+      program.programId
+    );
+    try {
+      await program.methods
+        .closeMemoryLog()
+        .accounts({
+          agent: retiredPda,
+          memoryLog: memLogPda,
+          attestation: attestPda,
+          round: roundPda,
+          authority: buyer.publicKey,
+        })
+        .rpc();
+      assert.fail("Should have held the log for the retention period");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("RetentionPeriodActive"));
+    }
+  });
+
+  it("Enforces canonical, reserved and paid agent names", async () => {
+    const initAccounts = (name: string) => ({
+      agent: PublicKey.findProgramAddressSync(
+        [Buffer.from("agent"), Buffer.from(name)],
+        program.programId
+      )[0],
+      platformConfig: configPda,
+      nameReservation: nameReservationPda(name)[0],
+      authority: buyer.publicKey,
+      systemProgram: SystemProgram.programId,
+      organization: null,
+    });
+
+    try {
+      await program.methods
+        .initializeAgent("Mixed-Case")
+        .accounts(initAccounts("Mixed-Case"))
+        .signers([buyer])
+        .rpc();
+      assert.fail("Should have rejected a non-canonical agent ID");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("InvalidAgentId"));
+    }
+
+    await program.methods
+      .setReservedNames(["admin"])
+      .accounts({ platformConfig: configPda, authority: authority.publicKey })
+      .rpc();
 
+    try {
+      await program.methods
+        .initializeAgent("admin")
+        .accounts(initAccounts("admin"))
+        .signers([buyer])
+        .rpc();
+      assert.fail("Should have rejected a reserved agent ID");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("NameUnavailable"));
+    }
+
+    const heldName = "held-name";
+    const treasuryBefore = await provider.connection.getBalance(
+      treasury.publicKey
+    );
     await program.methods
-      .initializePlatform(treasury.publicKey, 500, 500)
+      .reserveAgentName(heldName)
       .accounts({
+        nameReservation: nameReservationPda(heldName)[0],
+        agent: initAccounts(heldName).agent,
         platformConfig: configPda,
-        authority: authority.publicKey,
+        treasury: treasury.publicKey,
+        owner: attester.publicKey,
         systemProgram: SystemProgram.programId,
       })
+      .signers([attester])
       .rpc();
 
     const config = await program.account.platformConfig.fetch(configPda);
-    assert.equal(config.platformFeeBps, 500);
-    assert.equal(config.referralFeeBps, 500);
-    assert.equal(
-      (config.treasury as PublicKey).toBase58(),
-      treasury.publicKey.toBase58()
+    const treasuryAfter = await provider.connection.getBalance(
+      treasury.publicKey
     );
     assert.equal(
-      (config.authority as PublicKey).toBase58(),
-      authority.publicKey.toBase58()
+      treasuryAfter - treasuryBefore,
+      (config.nameReservationFee as BN).toNumber()
     );
-  });
 
-  it("Initializes an agent", async () => {
+    try {
+      await program.methods
+        .initializeAgent(heldName)
+        .accounts(initAccounts(heldName))
+        .signers([buyer])
+        .rpc();
+      assert.fail("Should have rejected a name reserved by someone else");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("NameUnavailable"));
+    }
+
     await program.methods
-      .initializeAgent(agentId)
-      .accounts({
-        agent: agentPda,
-        authority: authority.publicKey,
-        systemProgram: SystemProgram.programId,
-      })
+      .initializeAgent(heldName)
+      .accounts({ ...initAccounts(heldName), authority: attester.publicKey })
+      .signers([attester])
       .rpc();
 
-    const agent = await program.account.agentAccount.fetch(agentPda);
-    assert.equal(agent.agentId, agentId);
-    assert.equal((agent.reputation as BN).toNumber(), 0);
-    assert.equal((agent.totalLogs as BN).toNumber(), 0);
-    assert.equal((agent.totalAttestations as BN).toNumber(), 0);
+    try {
+      await program.methods
+        .releaseNameReservation()
+        .accounts({
+          nameReservation: nameReservationPda(heldName)[0],
+          owner: attester.publicKey,
+        })
+        .rpc();
+      assert.fail("Should have kept an unexpired reservation");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("NameReservationActive"));
+    }
   });
 
-  it("Logs a decision", async () => {
-    const inputData = "BTC at $97000, RSI oversold at 28";
-    const logicData = "Apply DCA strategy, buy 0.01 BTC";
+  it("Freezes an agent and records its appeal", async () => {
+    const evidence = Array.from(Buffer.alloc(32, 7));
+    const moderateAccounts = {
+      agent: agentPda,
+      platformConfig: configPda,
+      authority: authority.publicKey,
+    };
 
-    const slot = await provider.connection.getSlot();
-    const blockTime = await provider.connection.getBlockTime(slot);
+    await program.methods
+      .freezeAgent(3, evidence)
+      .accounts(moderateAccounts)
+      .rpc();
 
-    const buf = Buffer.alloc(8);
-    buf.writeBigInt64LE(BigInt(blockTime!));
-    const [memLogPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("memory"), agentPda.toBuffer(), buf],
-      program.programId
-    );
+    const frozen = await program.account.agentAccount.fetch(agentPda);
+    assert.isTrue(frozen.isFrozen as boolean);
+    assert.equal(frozen.moderationReason, 3);
 
-    const tx = await program.methods
-      .logDecision(inputData, logicData)
-      .accounts({
-        agent: agentPda,
-        memoryLog: memLogPda,
-        authority: authority.publicKey,
-        systemProgram: SystemProgram.programId,
-      })
+    const sequence = (frozen.totalLogs as BN).toNumber();
+    try {
+      await program.methods
+        .logDecision("While frozen", "Should fail", "")
+        .accounts({
+          agent: agentPda,
+          memoryLog: memoryLogPda(agentPda, sequence)[0],
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+          delegate: null,
+        })
+        .rpc();
+      assert.fail("Should have rejected a log on a frozen agent");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("AgentFrozen"));
+    }
+
+    const statement = Array.from(Buffer.alloc(32, 9));
+    await program.methods
+      .appealFreeze(statement)
+      .accounts({ agent: agentPda, authority: authority.publicKey })
       .rpc();
 
-    console.log("Decision logged:", tx);
+    try {
+      await program.methods
+        .appealFreeze(statement)
+        .accounts({ agent: agentPda, authority: authority.publicKey })
+        .rpc();
+      assert.fail("Should have allowed only one pending appeal");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("AppealPending"));
+    }
 
-    const memLog = await program.account.memoryLog.fetch(memLogPda);
-    assert.equal((memLog.agent as PublicKey).toBase58(), agentPda.toBase58());
-    assert.isFalse(memLog.isAttested as boolean);
-    assert.isNotNull(memLog.inputHash);
-    assert.isNotNull(memLog.logicHash);
-    assert.isNotNull(memLog.merkleRoot);
+    const appealed = await program.account.agentAccount.fetch(agentPda);
+    assert.deepEqual(appealed.appealHash as number[], statement);
 
-    memoryLogPubkey = memLogPda;
+    await program.methods
+      .unfreezeAgent(0, evidence)
+      .accounts(moderateAccounts)
+      .rpc();
 
-    const agent = await program.account.agentAccount.fetch(agentPda);
-    assert.equal((agent.totalLogs as BN).toNumber(), 1);
+    const unfrozen = await program.account.agentAccount.fetch(agentPda);
+    assert.isFalse(unfrozen.isFrozen as boolean);
+    assert.isNull(unfrozen.appealHash);
   });
 
-  it("Attests an outcome", async () => {
-    const outcomeData = "Bought at $97050, profit +2.3% after 4h";
-    const success = true;
-    const scoreDelta = 10;
+  it("Links memory logs to the active agent version", async () => {
+    const versionPda = (version: number) => {
+      const buf = Buffer.alloc(4);
+      buf.writeUInt32LE(version);
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("agent_version"), agentPda.toBuffer(), buf],
+        program.programId
+      );
+    };
+    const modelHash = Array.from(Buffer.alloc(32, 1));
+    const configHash = Array.from(Buffer.alloc(32, 2));
 
-    const [attestPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("attest"), memoryLogPubkey.toBuffer()],
-      program.programId
-    );
+    try {
+      await program.methods
+        .activateAgentVersion(2, modelHash, configHash)
+        .accounts({
+          agent: agentPda,
+          agentVersion: versionPda(2)[0],
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Should have required versions in sequence");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("InvalidAgentVersion"));
+    }
 
-    const tx = await program.methods
-      .attestOutcome(outcomeData, success, new BN(scoreDelta))
+    await program.methods
+      .activateAgentVersion(1, modelHash, configHash)
       .accounts({
         agent: agentPda,
-        memoryLog: memoryLogPubkey,
-        attestation: attestPda,
+        agentVersion: versionPda(1)[0],
         authority: authority.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .rpc();
 
-    console.log("Outcome attested:", tx);
+    const version = await program.account.agentVersion.fetch(versionPda(1)[0]);
+    assert.equal(version.version, 1);
+    assert.deepEqual(version.modelHash as number[], modelHash);
 
-    const attestation = await program.account.attestation.fetch(attestPda);
-    assert.isTrue(attestation.success as boolean);
-    assert.equal((attestation.scoreDelta as BN).toNumber(), scoreDelta);
+    const agent = await program.account.agentAccount.fetch(agentPda);
+    assert.equal(agent.activeVersion, 1);
 
-    const memLog = await program.account.memoryLog.fetch(memoryLogPubkey);
-    assert.isTrue(memLog.isAttested as boolean);
+    const [memLogPda] = memoryLogPda(agentPda, (agent.totalLogs as BN).toNumber());
+    await program.methods
+      .logDecision("New model online", "Same strategy", "")
+      .accounts({
+        agent: agentPda,
+        memoryLog: memLogPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        delegate: null,
+      })
+      .rpc();
 
-    const agent = await program.account.agentAccount.fetch(agentPda);
-    assert.equal((agent.reputation as BN).toNumber(), 10);
-    assert.equal((agent.totalAttestations as BN).toNumber(), 1);
-  });
+    const memLog = await program.account.memoryLog.fetch(memLogPda);
+    assert.equal(memLog.version, 1);
+    assert.isFalse(memLog.versionTallied as boolean);
 
-  it("Prevents double attestation", async () => {
-    const [attestPda] = PublicKey.findProgramAddressSync(
-      [Buffer.from("attest"), memoryLogPubkey.toBuffer()],
+    const seqBuf = Buffer.alloc(8);
+    seqBuf.writeBigUInt64LE(BigInt((agent.totalLogs as BN).toNumber() + 1));
+    const [batchPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("batch"), agentPda.toBuffer(), seqBuf],
       program.programId
     );
+    await program.methods
+      .logDecisionBatch([
+        { inputHash: Array(32).fill(7), logicHash: Array(32).fill(8) },
+      ])
+      .accounts({
+        agent: agentPda,
+        batch: batchPda,
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
 
-    try {
-      await program.methods
-        .attestOutcome("duplicate", true, new BN(5))
-        .accounts({
-          agent: agentPda,
-          memoryLog: memoryLogPubkey,
-          attestation: attestPda,
-          authority: authority.publicKey,
-          systemProgram: SystemProgram.programId,
-        })
-        .rpc();
-      assert.fail("Should have thrown");
-    } catch (err: any) {
-      assert.ok(err.toString().includes("already in use") || err.toString().includes("AlreadyAttested"));
-    }
+    const batch = await program.account.decisionBatch.fetch(batchPda);
+    assert.equal(batch.version, 1);
   });
 
   it("Registers a memory module", async () => {
@@ -239,8 +2071,11 @@ describe("AgentMemory Protocol", () => {
       .initializeAgent(buyerAgentId)
       .accounts({
         agent: buyerAgentPda,
+        platformConfig: configPda,
+        nameReservation: nameReservationPda(buyerAgentId)[0],
         authority: buyer.publicKey,
         systemProgram: SystemProgram.programId,
+        organization: null,
       })
       .signers([buyer])
       .rpc();
@@ -269,6 +2104,8 @@ describe("AgentMemory Protocol", () => {
         creatorWallet: creator.publicKey,
         referrerWallet: buyer.publicKey,
         systemProgram: SystemProgram.programId,
+        delegate: null,
+        organization: null,
       })
       .signers([buyer])
       .rpc();
@@ -347,6 +2184,8 @@ describe("AgentMemory Protocol", () => {
           creatorWallet: creator.publicKey,
           referrerWallet: buyer.publicKey,
           systemProgram: SystemProgram.programId,
+          delegate: null,
+          organization: null,
         })
         .signers([buyer])
         .rpc();
@@ -356,6 +2195,162 @@ describe("AgentMemory Protocol", () => {
     }
   });
 
+  it("Buys a module from an organisation budget", async () => {
+    const [modulePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("module"), Buffer.from(moduleId)],
+      program.programId
+    );
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("platform_config")],
+      program.programId
+    );
+    const orgId = "fleet-org";
+    const [orgPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("organization"), Buffer.from(orgId)],
+      program.programId
+    );
+    const fleetAgentId = "fleet-agent-001";
+    const [fleetAgentPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("agent"), Buffer.from(fleetAgentId)],
+      program.programId
+    );
+
+    await program.methods
+      .initializeOrganization(orgId)
+      .accounts({
+        organization: orgPda,
+        admin: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    await program.methods
+      .fundOrganization(new BN(0.2 * LAMPORTS_PER_SOL))
+      .accounts({
+        organization: orgPda,
+        funder: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+
+    try {
+      await program.methods
+        .initializeAgent(fleetAgentId)
+        .accounts({
+          agent: fleetAgentPda,
+          platformConfig: configPda,
+          nameReservation: nameReservationPda(fleetAgentId)[0],
+          authority: buyer.publicKey,
+          systemProgram: SystemProgram.programId,
+          organization: orgPda,
+        })
+        .signers([buyer])
+        .rpc();
+      assert.fail("Should have required an organisation admin");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("NotOrganizationAdmin"));
+    }
+
+    await program.methods
+      .initializeAgent(fleetAgentId)
+      .accounts({
+        agent: fleetAgentPda,
+        platformConfig: configPda,
+        nameReservation: nameReservationPda(fleetAgentId)[0],
+        authority: authority.publicKey,
+        systemProgram: SystemProgram.programId,
+        organization: orgPda,
+      })
+      .rpc();
+
+    const [purchasePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("purchase"), fleetAgentPda.toBuffer(), modulePda.toBuffer()],
+      program.programId
+    );
+    const orgBalBefore = await provider.connection.getBalance(orgPda);
+    const setSpendLimit = (lamports: number) =>
+      program.methods
+        .setMemberSpendLimit(new BN(lamports))
+        .accounts({ organization: orgPda, admin: authority.publicKey })
+        .rpc();
+    const purchase = () =>
+      program.methods
+        .purchaseModule(null)
+        .accounts({
+          moduleMetadata: modulePda,
+          platformConfig: configPda,
+          modulePurchase: purchasePda,
+          agent: fleetAgentPda,
+          buyer: authority.publicKey,
+          treasury: treasury.publicKey,
+          creatorWallet: creator.publicKey,
+          referrerWallet: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+          delegate: null,
+          organization: orgPda,
+        })
+        .rpc();
+
+    await setSpendLimit(50_000_000);
+    try {
+      await purchase();
+      assert.fail("Should have enforced the per-member spend limit");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("MemberSpendLimitExceeded"));
+    }
+    await setSpendLimit(100_000_000);
+    await purchase();
+
+    const orgBalAfter = await provider.connection.getBalance(orgPda);
+    assert.equal(orgBalBefore - orgBalAfter, 100_000_000);
+
+    await program.methods
+      .syncOrganizationStats()
+      .accounts({
+        organization: orgPda,
+        agent: fleetAgentPda,
+        platformConfig: configPda,
+      })
+      .rpc();
+
+    let org = await program.account.organization.fetch(orgPda);
+    assert.equal(org.agentCount, 1);
+    assert.equal((org.totalSpent as BN).toNumber(), 100_000_000);
+    assert.equal((org.totalLogs as BN).toNumber(), 0);
+    let fleetAgent = await program.account.agentAccount.fetch(fleetAgentPda);
+    assert.equal((fleetAgent.orgSpent as BN).toNumber(), 100_000_000);
+
+    try {
+      await program.methods
+        .removeFromOrganization()
+        .accounts({
+          organization: orgPda,
+          agent: fleetAgentPda,
+          admin: buyer.publicKey,
+        })
+        .signers([buyer])
+        .rpc();
+      assert.fail("Should have required an organisation admin");
+    } catch (err: any) {
+      assert.ok(err.toString().includes("NotOrganizationAdmin"));
+    }
+
+    await program.methods
+      .leaveOrganization()
+      .accounts({
+        organization: orgPda,
+        agent: fleetAgentPda,
+        authority: authority.publicKey,
+      })
+      .rpc();
+
+    org = await program.account.organization.fetch(orgPda);
+    assert.equal(org.agentCount, 0);
+    fleetAgent = await program.account.agentAccount.fetch(fleetAgentPda);
+    assert.isNull(fleetAgent.organization);
+    assert.equal((fleetAgent.orgSpent as BN).toNumber(), 0);
+  });
+
   it("Rejects module with price too low", async () => {
     const badModuleId = "bad-module";
     const [badModulePda] = PublicKey.findProgramAddressSync(